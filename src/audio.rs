@@ -0,0 +1,134 @@
+//! Sound effects, synthesised with the Web Audio API so that no audio files
+//! have to be shipped.
+
+use {
+    wasm_bindgen::prelude::*,
+    web_sys::{
+        AudioContext,
+        AudioContextState,
+        OscillatorType,
+    },
+};
+
+/// Everything the client makes a sound for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Sound {
+    Place,
+    Capture,
+    Pass,
+    Chat,
+    YourTurn,
+}
+
+/// One note of a sound, timed from when the sound starts.
+struct Tone {
+    frequency: f32,
+    start: f64,
+    duration: f64,
+    level: f32,
+    kind: OscillatorType,
+}
+
+impl Sound {
+    fn tones(self) -> Vec<Tone> {
+        let tone = |frequency, start, duration, level, kind| Tone { frequency, start, duration, level, kind };
+
+        match self {
+            Sound::Place => vec![
+                tone(180_f32, 0_f64, 0.12_f64, 1_f32, OscillatorType::Triangle),
+            ],
+            Sound::Capture => vec![
+                tone(660_f32, 0_f64, 0.08_f64, 0.6_f32, OscillatorType::Square),
+                tone(330_f32, 0.08_f64, 0.14_f64, 0.6_f32, OscillatorType::Square),
+            ],
+            Sound::Pass => vec![
+                tone(440_f32, 0_f64, 0.25_f64, 0.5_f32, OscillatorType::Sine),
+            ],
+            Sound::Chat => vec![
+                tone(880_f32, 0_f64, 0.06_f64, 0.4_f32, OscillatorType::Sine),
+                tone(1320_f32, 0.07_f64, 0.08_f64, 0.4_f32, OscillatorType::Sine),
+            ],
+            Sound::YourTurn => vec![
+                tone(523_f32, 0_f64, 0.12_f64, 0.7_f32, OscillatorType::Sine),
+                tone(659_f32, 0.12_f64, 0.12_f64, 0.7_f32, OscillatorType::Sine),
+                tone(784_f32, 0.24_f64, 0.2_f64, 0.7_f32, OscillatorType::Sine),
+            ],
+        }
+    }
+}
+
+/// Plays sounds at a volume between 0 and 1, unless muted.
+pub struct Audio {
+    context: Option<AudioContext>,
+    pub volume: f64,
+    pub muted: bool,
+}
+
+impl Default for Audio {
+    fn default() -> Audio {
+        Audio {
+            context: None,
+            volume: 0.5_f64,
+            muted: false,
+        }
+    }
+}
+
+impl Audio {
+    /// Creates or wakes the audio context. Browsers only allow that from a
+    /// user gesture, so this is called from one before anything is played.
+    pub fn unlock(&mut self) -> Result<(), JsValue> {
+        let context = match self.context.take() {
+            Some(context) => context,
+            None => AudioContext::new()?,
+        };
+
+        if context.state() == AudioContextState::Suspended {
+            let _ = context.resume()?;
+        }
+
+        self.context = Some(context);
+
+        Ok(())
+    }
+
+    pub fn play(&mut self, sound: Sound) -> Result<(), JsValue> {
+        if self.muted || self.volume <= 0_f64 {
+            return Ok(());
+        }
+
+        // Without an unlocked context there is nothing that may play yet.
+        let context = match self.context.as_ref() {
+            Some(context) if context.state() == AudioContextState::Running => context,
+            _ => return Ok(()),
+        };
+
+        let now = context.current_time();
+
+        for tone in sound.tones() {
+            let start = now + tone.start;
+            let end = start + tone.duration;
+
+            let oscillator = context.create_oscillator()?;
+            oscillator.set_type(tone.kind);
+            oscillator.frequency()
+                .set_value(tone.frequency);
+
+            // A short attack and exponential release keep the tones from
+            // clicking.
+            let gain = context.create_gain()?;
+            let level = (self.volume as f32 * tone.level).max(0.0001_f32);
+            gain.gain().set_value_at_time(0.0001_f32, start)?;
+            gain.gain().exponential_ramp_to_value_at_time(level, start + 0.005_f64)?;
+            gain.gain().exponential_ramp_to_value_at_time(0.0001_f32, end)?;
+
+            oscillator.connect_with_audio_node(&gain)?;
+            gain.connect_with_audio_node(&context.destination())?;
+
+            oscillator.start_with_when(start)?;
+            oscillator.stop_with_when(end)?;
+        }
+
+        Ok(())
+    }
+}
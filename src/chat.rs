@@ -0,0 +1,240 @@
+//! Chat input handling: slash-commands are picked out here before anything
+//! is sent to the room, and board coordinates are found in what comes back.
+
+use {
+    crate::{
+        dom,
+        game::parse_coordinate,
+        i18n::{
+            Locale,
+            Message,
+        },
+    },
+    rengo_common::networking::{
+        ChatChannel,
+        ChatMessage,
+    },
+};
+
+/// Commands listed by `/help`, with what they do.
+pub const COMMANDS: [(&str, Message<'static>); 6] = [
+    ("/pass", Message::HelpPass),
+    ("/resign", Message::HelpResign),
+    ("/who", Message::HelpWho),
+    ("/clear", Message::HelpClear),
+    ("/me <text>", Message::HelpMe),
+    ("/help", Message::HelpHelp),
+];
+
+/// What a line typed into the chat asks for.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Command {
+    Say(String),
+    Me(String),
+    Pass,
+    Resign,
+    Who,
+    Clear,
+    Help,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CommandError {
+    Unknown(String),
+    MissingText(String),
+}
+
+/// Parses a line of chat input. Anything not starting with a slash is said
+/// as is, and a doubled slash sends a line that starts with one.
+pub fn parse(input: &str) -> Result<Command, CommandError> {
+    let input = input.trim();
+
+    if input.starts_with("//") {
+        return Ok(Command::Say(input[1..].to_string()));
+    }
+
+    if ! input.starts_with('/') {
+        return Ok(Command::Say(input.to_string()));
+    }
+
+    let (name, text) = match input.find(char::is_whitespace) {
+        Some(split) => (&input[..split], input[split..].trim()),
+        None => (input, ""),
+    };
+
+    match name.to_lowercase().as_str() {
+        "/pass" => Ok(Command::Pass),
+        "/resign" => Ok(Command::Resign),
+        "/who" => Ok(Command::Who),
+        "/clear" => Ok(Command::Clear),
+        "/help" => Ok(Command::Help),
+        "/me" if text.is_empty() => Err(CommandError::MissingText(name.to_string())),
+        "/me" => Ok(Command::Me(text.to_string())),
+        _ => Err(CommandError::Unknown(name.to_string())),
+    }
+}
+
+/// Board coordinates mentioned in `text`, as the byte range of each word
+/// and the intersection it names on a board with `side_length` lines.
+pub fn coordinates(text: &str, side_length: usize) -> Vec<(usize, usize, (usize, usize))> {
+    let mut found = Vec::new();
+    let mut start = None;
+
+    for (i, c) in text.char_indices().chain(std::iter::once((text.len(), ' '))) {
+        if c.is_ascii_alphanumeric() {
+            start = start.or(Some(i));
+            continue;
+        }
+
+        // Words that merely contain letters are not coordinates, so only
+        // whole runs of ASCII letters and digits are looked at.
+        if let Some(word_start) = start.take() {
+            let boundary = ! text[..word_start]
+                .chars()
+                .last()
+                .is_some_and(char::is_alphanumeric)
+                && ! c.is_alphanumeric();
+
+            if boundary {
+                if let Some(position) = parse_coordinate(&text[word_start..i], side_length) {
+                    found.push((word_start, i, position));
+                }
+            }
+        }
+    }
+
+    found
+}
+
+/// The chat pane line for `message`, stamped with `time` as shown and
+/// `datetime` for machines. Coordinates in the text become buttons that
+/// point the intersection out on a board with `side_length` lines.
+pub fn line(message: &ChatMessage, time: &str, datetime: &str, side_length: usize, locale: Locale) -> dom::Element {
+    let mut line = dom::element("div");
+
+    let time = dom::element("time")
+        .class("chat-time text-muted small mr-1")
+        .attribute("datetime", datetime)
+        .text(time);
+
+    let body_text = match (&message.channel, &message.sender, message.emote) {
+        (ChatChannel::System, _, _) | (_, None, _) => {
+            line = line.class("chat-message chat-system text-muted font-italic")
+                .child(time);
+            message.body.clone()
+        }
+        (_, Some(sender), true) => {
+            line = line.class("chat-message chat-emote font-italic")
+                .child(time);
+            format!("* {} {}", sender, message.body)
+        }
+        (channel, Some(sender), false) => {
+            line = line.class("chat-message")
+                .child(time);
+
+            if *channel == ChatChannel::Spectators {
+                line = line.child(dom::element("span")
+                    .class("chat-channel badge badge-secondary mr-1")
+                    .text(locale.text(Message::SpectatorChannel)));
+            }
+
+            line = line.child(dom::element("strong")
+                .class("chat-sender mr-1")
+                .text(format!("{}:", sender)));
+
+            message.body.clone()
+        }
+    };
+
+    let mut body = dom::element("span")
+        .class("chat-body");
+    let mut written = 0;
+
+    for (start, end, (x, y)) in coordinates(&body_text, side_length) {
+        body = body.text(&body_text[written..start])
+            .child(dom::element("button")
+                .class("chat-coordinate btn btn-link p-0 align-baseline")
+                .attribute("type", "button")
+                .attribute("data-x", x.to_string())
+                .attribute("data-y", y.to_string())
+                .text(&body_text[start..end]));
+
+        written = end;
+    }
+
+    line.child(body.text(&body_text[written..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dom::tests::{
+        tags,
+        text_content,
+    };
+
+    fn message(sender: Option<&str>, channel: ChatChannel, body: &str, emote: bool) -> ChatMessage {
+        ChatMessage {
+            sender: sender.map(String::from),
+            timestamp: 0,
+            channel,
+            body: body.to_string(),
+            emote,
+        }
+    }
+
+    #[test]
+    fn parse_recognises_commands() {
+        assert_eq!(parse("/pass"), Ok(Command::Pass));
+        assert_eq!(parse(" /WHO "), Ok(Command::Who));
+        assert_eq!(parse("/me waves"), Ok(Command::Me(String::from("waves"))));
+        assert_eq!(parse("/me"), Err(CommandError::MissingText(String::from("/me"))));
+        assert_eq!(parse("/resgin"), Err(CommandError::Unknown(String::from("/resgin"))));
+        assert_eq!(parse("/resign"), Ok(Command::Resign));
+    }
+
+    #[test]
+    fn parse_says_everything_else() {
+        assert_eq!(parse("hello /pass"), Ok(Command::Say(String::from("hello /pass"))));
+        assert_eq!(parse("//pass"), Ok(Command::Say(String::from("/pass"))));
+    }
+
+    #[test]
+    fn coordinates_are_found_in_text() {
+        assert_eq!(coordinates("D4 or q16, not Z9", 19),
+                   vec![(0, 2, (3, 3)), (6, 9, (16, 15))]);
+        assert_eq!(coordinates("take D4!", 9), vec![(5, 7, (3, 3))]);
+        assert_eq!(coordinates("D40 AD4 D4x", 19), Vec::new());
+        assert_eq!(coordinates("éD4", 19), Vec::new());
+    }
+
+    #[test]
+    fn line_sets_apart_spectators_and_the_system() {
+        let spectator = message(Some("bob"), ChatChannel::Spectators, "hi", false);
+        let line = line(&spectator, "12:00", "", 19, Locale::English);
+
+        assert_eq!(tags(&line), vec!["div", "time", "span", "strong", "span"]);
+        assert_eq!(text_content(&line), "12:00spectatorsbob:hi");
+
+        let notice = message(None, ChatChannel::System, "bob left", false);
+        assert_eq!(text_content(&super::line(&notice, "", "", 19, Locale::English)), "bob left");
+    }
+
+    #[test]
+    fn line_links_coordinates() {
+        let chat = message(Some("alice"), ChatChannel::Room, "<b>D4</b>?", false);
+        let line = line(&chat, "", "", 19, Locale::English);
+
+        assert_eq!(tags(&line), vec!["div", "time", "strong", "span", "button"]);
+        assert_eq!(text_content(&line), "alice:<b>D4</b>?");
+    }
+
+    #[test]
+    fn line_only_shows_marked_emotes_as_such() {
+        let emote = message(Some("alice"), ChatChannel::Room, "waves", true);
+        assert_eq!(text_content(&line(&emote, "", "", 19, Locale::English)), "* alice waves");
+
+        let said = message(Some("alice"), ChatChannel::Room, "/me waves", false);
+        assert_eq!(text_content(&line(&said, "", "", 19, Locale::English)), "alice:/me waves");
+    }
+}
@@ -0,0 +1,192 @@
+//! Typed DOM building. Tag and attribute names are fixed at compile time,
+//! while strings from the server only ever become text nodes or attribute
+//! values, so they are never parsed as markup.
+
+use wasm_bindgen::prelude::*;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Node {
+    Element(Element),
+    Text(String),
+}
+
+/// An element to be created, with its attributes and children.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Element {
+    pub tag: &'static str,
+    pub attributes: Vec<(&'static str, String)>,
+    pub children: Vec<Node>,
+}
+
+pub fn element(tag: &'static str) -> Element {
+    Element {
+        tag,
+        attributes: Vec::new(),
+        children: Vec::new(),
+    }
+}
+
+/// Attributes the client sets. Event handler attributes are not among
+/// them, since their values are script; listeners are attached with
+/// closures instead.
+const ATTRIBUTES: &[&str] = &[
+    "aria-describedby",
+    "aria-labelledby",
+    "aria-modal",
+    "class",
+    "data-action",
+    "data-room",
+    "data-spectate",
+    "data-x",
+    "data-y",
+    "datetime",
+    "disabled",
+    "for",
+    "id",
+    "lang",
+    "placeholder",
+    "role",
+    "selected",
+    "tabindex",
+    "type",
+    "value",
+];
+
+impl Element {
+    /// Sets an attribute, which has to be one of `ATTRIBUTES`. Names are
+    /// fixed in the source, so debug builds and tests are enough to catch
+    /// one that is not.
+    pub fn attribute(mut self, name: &'static str, value: impl Into<String>) -> Element {
+        debug_assert!(ATTRIBUTES.contains(&name), "attribute {} is not allowed", name);
+
+        self.attributes.push((name, value.into()));
+        self
+    }
+
+    pub fn id(self, id: impl Into<String>) -> Element {
+        self.attribute("id", id)
+    }
+
+    pub fn class(self, class: &'static str) -> Element {
+        self.attribute("class", class)
+    }
+
+    pub fn text(mut self, text: impl Into<String>) -> Element {
+        self.children.push(Node::Text(text.into()));
+        self
+    }
+
+    pub fn child(mut self, child: Element) -> Element {
+        self.children.push(Node::Element(child));
+        self
+    }
+
+    /// Creates the element and everything in it in the page's document.
+    pub fn build(&self) -> Result<web_sys::Element, JsValue> {
+        let document = web_sys::window()
+            .unwrap()
+            .document()
+            .unwrap();
+
+        let built = document.create_element(self.tag)?;
+
+        for (name, value) in self.attributes.iter() {
+            built.set_attribute(name, value)?;
+        }
+
+        for child in self.children.iter() {
+            let node: web_sys::Node = match child {
+                Node::Element(element) => element.build()?.into(),
+                Node::Text(text) => document.create_text_node(text).into(),
+            };
+
+            built.append_child(&node)?;
+        }
+
+        Ok(built)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    /// Tags of every element in the tree, depth first.
+    pub fn tags(element: &Element) -> Vec<&'static str> {
+        let mut found = vec![element.tag];
+
+        for child in element.children.iter() {
+            if let Node::Element(child) = child {
+                found.extend(tags(child));
+            }
+        }
+
+        found
+    }
+
+    /// All text in the tree, as a screen would show it.
+    pub fn text_content(element: &Element) -> String {
+        element.children
+            .iter()
+            .map(|child| match child {
+                Node::Element(element) => text_content(element),
+                Node::Text(text) => text.clone(),
+            })
+            .collect()
+    }
+
+    const HOSTILE: &str = r#"</th><img src=x onerror="alert(1)"><script>alert(2)</script>"#;
+
+    #[test]
+    fn hostile_strings_stay_text_and_values() {
+        let row = element("tr")
+            .child(element("th").text(HOSTILE))
+            .child(element("th")
+                .child(element("button")
+                    .attribute("data-room", HOSTILE)
+                    .text("Join")));
+
+        assert_eq!(tags(&row), vec!["tr", "th", "th", "button"]);
+        assert_eq!(row.children[0], Node::Element(element("th").text(HOSTILE)));
+        assert_eq!(text_content(&row), format!("{}Join", HOSTILE));
+        assert!(row.attributes.is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn event_handler_attributes_are_refused() {
+        element("img").attribute("onError", "alert(1)");
+    }
+
+    /// Builds into a real document, run with `wasm-pack test --headless
+    /// --firefox`.
+    #[cfg(target_arch = "wasm32")]
+    mod web {
+        use super::*;
+        use wasm_bindgen_test::*;
+
+        wasm_bindgen_test_configure!(run_in_browser);
+
+        #[wasm_bindgen_test]
+        fn hostile_strings_stay_inert_in_the_page() {
+            let built = element("tr")
+                .child(element("th").text(HOSTILE))
+                .child(element("th")
+                    .child(element("button")
+                        .attribute("data-room", HOSTILE)
+                        .text("Join")))
+                .build()
+                .unwrap();
+
+            assert_eq!(built.query_selector_all("img, script").unwrap().length(), 0);
+            assert_eq!(built.get_elements_by_tag_name("th").length(), 2);
+            assert_eq!(built.text_content().unwrap(), format!("{}Join", HOSTILE));
+
+            let button = built.query_selector("button")
+                .unwrap()
+                .unwrap();
+
+            assert_eq!(button.get_attribute("data-room").unwrap(), HOSTILE);
+        }
+    }
+}
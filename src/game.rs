@@ -0,0 +1,683 @@
+//! Client-side game state and board rules.
+//!
+//! Nothing in here touches the DOM, so it builds and is tested natively. The
+//! browser layer feeds every server message through `State::apply` and then
+//! draws whatever the state ends up as.
+
+use rengo_common::{
+    networking::{
+        Room,
+        ServerMessage,
+    },
+    logic::{
+        Position,
+        Stone,
+    },
+};
+
+const ALPHABET: [char; 26] = ['A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J', 'K', 'L', 'M',
+                              'N', 'O', 'P', 'Q', 'R', 'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z'];
+
+/// Reasons the client refuses to send a move before asking the server.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IllegalMove {
+    NotSeated,
+    NotYourTurn,
+    OutOfBounds,
+    Occupied,
+    Suicide,
+}
+
+/// Everything the client knows about the room it is in.
+#[derive(Default)]
+pub struct State {
+    pub room: Option<Room>,
+    pub spectating: bool,
+}
+
+impl State {
+    /// Folds a server message into the state. Messages that only concern the
+    /// view, such as chat or login errors, leave it untouched.
+    pub fn apply(&mut self, message: &ServerMessage) {
+        match message {
+            ServerMessage::LoginResponse(Ok(room)) =>
+                self.room = Some(room.clone()),
+            ServerMessage::RoomCreateResponse(Ok(Some(room))) =>
+                self.room = Some(room.clone()),
+            _ => (),
+        }
+
+        let room = match self.room.as_mut() {
+            Some(room) => room,
+            None => return,
+        };
+
+        match message {
+            ServerMessage::PlaceResponse(Ok(action)) => {
+                if let Some((position, stone)) = &action.0 {
+                    let (x, y) = (position.x() as usize, position.y() as usize);
+
+                    if let Some(spot) = room.board.stones
+                        .get_mut(y)
+                        .and_then(|row| row.get_mut(x))
+                    {
+                        *spot = *stone;
+                    }
+                }
+            }
+            ServerMessage::PlayerAdd(player) => {
+                room.players.retain(|(username, _)| *username != player.username);
+                room.players.push((player.username.clone(), player.clone()));
+            }
+            ServerMessage::PlayerRemove(username) =>
+                room.players.retain(|(other, _)| other != username),
+            ServerMessage::SpectatorAdd(username) if ! room.spectators.contains(username) =>
+                room.spectators.push(username.clone()),
+            ServerMessage::SpectatorRemove(username) =>
+                room.spectators.retain(|other| other != username),
+            ServerMessage::NextTurn(username) =>
+                room.current_player = username.clone(),
+            _ => (),
+        }
+    }
+
+    /// Forgets the room, as after the connection closes.
+    pub fn reset(&mut self) {
+        self.room = None;
+    }
+
+    /// Stone colour of the local player, or `None` when spectating or not in
+    /// a room.
+    pub fn self_stone(&self) -> Option<Stone> {
+        if self.spectating {
+            return None;
+        }
+
+        let room = self.room.as_ref()?;
+
+        room.players
+            .iter()
+            .find(|(username, _)| *username == room.self_player)
+            .map(|(_, player)| player.stone)
+    }
+
+    pub fn is_self_turn(&self) -> bool {
+        match self.room.as_ref() {
+            Some(room) => ! self.spectating && room.current_player == room.self_player,
+            None => false,
+        }
+    }
+
+    /// Checks what the client can know locally about placing a stone at
+    /// `position`. The server stays authoritative, e.g. for ko.
+    pub fn check_move(&self, position: &Position<u32>) -> Result<(), IllegalMove> {
+        let stone = self.self_stone()
+            .ok_or(IllegalMove::NotSeated)?;
+
+        if ! self.is_self_turn() {
+            return Err(IllegalMove::NotYourTurn);
+        }
+
+        let stones = &self.room
+            .as_ref()
+            .unwrap()
+            .board
+            .stones;
+
+        check_placement(stones, stone, position.x() as usize, position.y() as usize)
+    }
+}
+
+/// Board-level legality of placing `stone` at (`x`, `y`): the point has to be
+/// on the board and empty, and the stone must either keep a liberty or
+/// capture something.
+pub fn check_placement(stones: &[Vec<Option<Stone>>], stone: Stone, x: usize, y: usize) -> Result<(), IllegalMove> {
+    match stones.get(y).and_then(|row| row.get(x)) {
+        None => return Err(IllegalMove::OutOfBounds),
+        Some(Some(_)) => return Err(IllegalMove::Occupied),
+        Some(None) => (),
+    }
+
+    let mut after = stones.to_vec();
+    after[y][x] = Some(stone);
+
+    for (n_x, n_y) in neighbours(&after, x, y) {
+        if let Some(other) = &after[n_y][n_x] {
+            if *other != stone && ! has_liberty(&after, n_x, n_y) {
+                return Ok(());
+            }
+        }
+    }
+
+    if has_liberty(&after, x, y) {
+        Ok(())
+    } else {
+        Err(IllegalMove::Suicide)
+    }
+}
+
+fn neighbours(stones: &[Vec<Option<Stone>>], x: usize, y: usize) -> Vec<(usize, usize)> {
+    let mut neighbours = Vec::with_capacity(4);
+
+    if x > 0 {
+        neighbours.push((x - 1, y));
+    }
+    if y > 0 {
+        neighbours.push((x, y - 1));
+    }
+    if x + 1 < stones[y].len() {
+        neighbours.push((x + 1, y));
+    }
+    if y + 1 < stones.len() {
+        neighbours.push((x, y + 1));
+    }
+
+    neighbours
+}
+
+/// Whether the group containing the stone at (`x`, `y`) touches an empty point.
+fn has_liberty(stones: &[Vec<Option<Stone>>], x: usize, y: usize) -> bool {
+    let colour = &stones[y][x];
+    let mut visited = vec![vec![false; stones[0].len()]; stones.len()];
+    let mut pending = vec![(x, y)];
+    visited[y][x] = true;
+
+    while let Some((x, y)) = pending.pop() {
+        for (n_x, n_y) in neighbours(stones, x, y) {
+            if stones[n_y][n_x].is_none() {
+                return true;
+            }
+
+            if ! visited[n_y][n_x] && stones[n_y][n_x] == *colour {
+                visited[n_y][n_x] = true;
+                pending.push((n_x, n_y));
+            }
+        }
+    }
+
+    false
+}
+
+/// Column label used for index `i`, matching the letters drawn on the board.
+pub fn column_label(i: usize) -> String {
+    let nest = (i as f64 / 26_f64).ceil().max(1_f64);
+
+    let mut indicator = String::new();
+    for _ in 0..nest as usize {
+        indicator += &ALPHABET[i % 26].to_string();
+    }
+
+    indicator
+}
+
+/// Coordinate of an intersection as players read it, such as "D4".
+pub fn coordinate_label(x: usize, y: usize) -> String {
+    format!("{}{}", column_label(x), y + 1)
+}
+
+/// Parses a coordinate such as "D4" into a column and row on a board with
+/// `side_length` lines. Rows count from 1 at the top, as they are drawn.
+pub fn parse_coordinate(text: &str, side_length: usize) -> Option<(usize, usize)> {
+    let text = text.trim().to_uppercase();
+    let split = text.find(|c: char| c.is_ascii_digit())?;
+    let (letters, digits) = text.split_at(split);
+
+    let x = (0..side_length).find(|i| column_label(*i) == letters)?;
+    let y = digits.parse::<usize>().ok()?;
+
+    if y == 0 || y > side_length {
+        return None;
+    }
+
+    Some((x, y - 1))
+}
+
+/// Pixel layout of a board drawn into a square of `board_size` CSS pixels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Geometry {
+    pub board_size: f64,
+    pub side_length: usize,
+    pub inner_begin: f64,
+    pub inner_size: f64,
+    pub line_space: f64,
+}
+
+impl Geometry {
+    pub fn new(board_size: f64, side_length: usize) -> Geometry {
+        let inner_size = 0.74_f64 * board_size;
+        let inner_begin = (board_size - inner_size) / 2_f64;
+        let line_space = inner_size / (side_length as f64 - 1_f64);
+
+        Geometry {
+            board_size,
+            side_length,
+            inner_begin,
+            inner_size,
+            line_space,
+        }
+    }
+
+    /// Intersection nearest to the point (`x_i`, `y_i`), if it is on the board.
+    pub fn piece_position(&self, x_i: i32, y_i: i32) -> Option<Position<u32>> {
+        let inner_begin = self.inner_begin;
+        let inner_size = self.inner_size;
+        let line_space = self.line_space;
+
+        if x_i < (inner_begin - 4_f64 * inner_begin / 9_f64) as i32
+        || x_i > (inner_begin + inner_size + 1_f64 * inner_begin / 18_f64) as i32
+        || y_i < (inner_begin - 4_f64 * inner_begin / 9_f64) as i32
+        || y_i > (inner_begin + inner_size + 1_f64 * inner_begin / 18_f64) as i32
+        {
+            return None;
+        }
+
+        let x = x_i as f64 - inner_begin + line_space / 2_f64;
+        let y = y_i as f64 - inner_begin + line_space / 2_f64;
+        let p_x = (x / line_space).max(0_f64) as u32;
+        let p_y = (y / line_space).max(0_f64) as u32;
+
+        if p_x as usize >= self.side_length || p_y as usize >= self.side_length {
+            return None;
+        }
+
+        Some(Position(p_x, p_y))
+    }
+}
+
+/// How the board is turned on screen: mirrored left to right first, then
+/// rotated clockwise by a number of quarter turns. Only the drawing changes;
+/// moves are always sent in board coordinates.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Orientation {
+    pub quarter_turns: u8,
+    pub mirrored: bool,
+}
+
+impl Orientation {
+    pub fn rotate_clockwise(&mut self) {
+        self.quarter_turns = (self.quarter_turns + 1) % 4;
+    }
+
+    /// Mirrors the board as it currently appears, left to right.
+    pub fn flip_horizontal(&mut self) {
+        self.quarter_turns = (4 - self.quarter_turns) % 4;
+        self.mirrored = ! self.mirrored;
+    }
+
+    /// Mirrors the board as it currently appears, top to bottom.
+    pub fn flip_vertical(&mut self) {
+        self.flip_horizontal();
+        self.quarter_turns = (self.quarter_turns + 2) % 4;
+    }
+
+    /// Whether board columns run across the screen as rows.
+    pub fn swaps_axes(&self) -> bool {
+        self.quarter_turns % 2 == 1
+    }
+
+    /// Where the intersection at board (`x`, `y`) is drawn.
+    pub fn to_view(self, x: usize, y: usize, side_length: usize) -> (usize, usize) {
+        let last = side_length.saturating_sub(1);
+        let (mut x, mut y) = if self.mirrored { (last - x, y) } else { (x, y) };
+
+        for _ in 0..self.quarter_turns {
+            let turned = (last - y, x);
+            x = turned.0;
+            y = turned.1;
+        }
+
+        (x, y)
+    }
+
+    /// Board intersection drawn at view (`x`, `y`).
+    pub fn to_board(self, x: usize, y: usize, side_length: usize) -> (usize, usize) {
+        let last = side_length.saturating_sub(1);
+        let (mut x, mut y) = (x, y);
+
+        for _ in 0..self.quarter_turns {
+            let turned = (y, last - x);
+            x = turned.0;
+            y = turned.1;
+        }
+
+        if self.mirrored { (last - x, y) } else { (x, y) }
+    }
+
+    /// A square board grid rearranged into the order it is drawn in.
+    pub fn view_grid<T: Clone>(&self, grid: &[Vec<T>]) -> Vec<Vec<T>> {
+        let side_length = grid.len();
+
+        (0..side_length).map(|y| {
+            (0..side_length).map(|x| {
+                let (b_x, b_y) = self.to_board(x, y, side_length);
+                grid[b_y][b_x].clone()
+            }).collect()
+        }).collect()
+    }
+
+    /// Label drawn above and below view column `i`.
+    pub fn column_label(&self, i: usize, side_length: usize) -> String {
+        let (x, y) = self.to_board(i, 0, side_length);
+
+        if self.swaps_axes() {
+            (y + 1).to_string()
+        } else {
+            column_label(x)
+        }
+    }
+
+    /// Label drawn beside view row `i`.
+    pub fn row_label(&self, i: usize, side_length: usize) -> String {
+        let (x, y) = self.to_board(0, i, side_length);
+
+        if self.swaps_axes() {
+            column_label(x)
+        } else {
+            (y + 1).to_string()
+        }
+    }
+}
+
+/// Largest zoom factor the board view allows.
+pub const MAX_ZOOM: f64 = 4_f64;
+
+/// Zoom and pan applied to the board canvas: a board point `p` is drawn at
+/// `p * zoom + offset`. The view never zooms out past the whole board, nor
+/// pans any part of the canvas off it.
+///
+/// The zoom is kept as its base-2 logarithm, so the default view is the
+/// identity.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct View {
+    pub zoom_exponent: f64,
+    pub offset_x: f64,
+    pub offset_y: f64,
+}
+
+impl View {
+    pub fn is_identity(&self) -> bool {
+        *self == View::default()
+    }
+
+    pub fn zoom(&self) -> f64 {
+        self.zoom_exponent.exp2()
+    }
+
+    /// Board point under the canvas point (`x`, `y`).
+    pub fn to_board(self, x: f64, y: f64) -> (f64, f64) {
+        ((x - self.offset_x) / self.zoom(),
+         (y - self.offset_y) / self.zoom())
+    }
+
+    /// Scales the view by `factor`, keeping the board point under the
+    /// canvas point (`x`, `y`) where it is.
+    pub fn zoom_at(&mut self, factor: f64, x: f64, y: f64, board_size: f64) {
+        let zoom = (self.zoom() * factor).clamp(1_f64, MAX_ZOOM);
+        let (board_x, board_y) = self.to_board(x, y);
+
+        self.zoom_exponent = zoom.log2();
+        self.offset_x = x - board_x * zoom;
+        self.offset_y = y - board_y * zoom;
+
+        self.clamp(board_size);
+    }
+
+    pub fn pan(&mut self, dx: f64, dy: f64, board_size: f64) {
+        self.offset_x += dx;
+        self.offset_y += dy;
+
+        self.clamp(board_size);
+    }
+
+    /// Keeps the same part of the board in view after the canvas changes size.
+    pub fn resize(&mut self, from: f64, to: f64) {
+        if from > 0_f64 {
+            self.offset_x *= to / from;
+            self.offset_y *= to / from;
+        }
+
+        self.clamp(to);
+    }
+
+    fn clamp(&mut self, board_size: f64) {
+        let min = board_size * (1_f64 - self.zoom());
+
+        self.offset_x = self.offset_x.max(min).min(0_f64);
+        self.offset_y = self.offset_y.max(min).min(0_f64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rengo_common::{
+        networking::Player,
+        logic::{
+            Board,
+            Move,
+        },
+    };
+
+    fn room(side_length: usize) -> Room {
+        Room {
+            current_player: String::from("alice"),
+            self_player: String::from("alice"),
+            players: vec![
+                (String::from("alice"), Player { username: String::from("alice"), stone: Stone::Black }),
+                (String::from("bob"), Player { username: String::from("bob"), stone: Stone::White }),
+            ],
+            spectators: Vec::new(),
+            board: Board {
+                stones: vec![vec![None; side_length]; side_length],
+            },
+        }
+    }
+
+    fn joined(side_length: usize) -> State {
+        let mut state = State::default();
+        state.apply(&ServerMessage::LoginResponse(Ok(room(side_length))));
+        state
+    }
+
+    fn place(state: &mut State, x: u8, y: u8, stone: Option<Stone>) {
+        state.apply(&ServerMessage::PlaceResponse(Ok(Move(Some((Position(x, y), stone)), None))));
+    }
+
+    #[test]
+    fn apply_tracks_room_updates() {
+        let mut state = joined(9);
+
+        place(&mut state, 2, 3, Some(Stone::Black));
+        state.apply(&ServerMessage::NextTurn(String::from("bob")));
+        state.apply(&ServerMessage::PlayerAdd(Player { username: String::from("carol"), stone: Stone::Black }));
+        state.apply(&ServerMessage::PlayerRemove(String::from("bob")));
+        state.apply(&ServerMessage::SpectatorAdd(String::from("dave")));
+
+        let room = state.room.as_ref().unwrap();
+        assert_eq!(room.board.stones[3][2], Some(Stone::Black));
+        assert_eq!(room.current_player, "bob");
+        let players: Vec<&str> = room.players.iter().map(|(username, _)| username.as_str()).collect();
+        assert_eq!(players, vec!["alice", "carol"]);
+        assert_eq!(room.spectators, vec![String::from("dave")]);
+
+        place(&mut state, 2, 3, None);
+        assert_eq!(state.room.as_ref().unwrap().board.stones[3][2], None);
+    }
+
+    #[test]
+    fn apply_ignores_room_messages_before_login() {
+        let mut state = State::default();
+
+        place(&mut state, 0, 0, Some(Stone::Black));
+        state.apply(&ServerMessage::NextTurn(String::from("bob")));
+
+        assert!(state.room.is_none());
+    }
+
+    #[test]
+    fn apply_ignores_out_of_range_placements() {
+        let mut state = joined(9);
+
+        place(&mut state, 9, 9, Some(Stone::Black));
+
+        assert!(state.room.as_ref().unwrap().board.stones.iter().flatten().all(Option::is_none));
+    }
+
+    #[test]
+    fn check_move_requires_seat_and_turn() {
+        let mut state = joined(9);
+        assert_eq!(state.check_move(&Position(4, 4)), Ok(()));
+
+        state.apply(&ServerMessage::NextTurn(String::from("bob")));
+        assert_eq!(state.check_move(&Position(4, 4)), Err(IllegalMove::NotYourTurn));
+
+        state.spectating = true;
+        assert_eq!(state.check_move(&Position(4, 4)), Err(IllegalMove::NotSeated));
+    }
+
+    #[test]
+    fn check_move_rejects_occupied_and_out_of_bounds() {
+        let mut state = joined(9);
+        place(&mut state, 4, 4, Some(Stone::White));
+
+        assert_eq!(state.check_move(&Position(4, 4)), Err(IllegalMove::Occupied));
+        assert_eq!(state.check_move(&Position(9, 0)), Err(IllegalMove::OutOfBounds));
+    }
+
+    #[test]
+    fn check_move_rejects_suicide_unless_it_captures() {
+        let mut state = joined(9);
+        place(&mut state, 1, 0, Some(Stone::White));
+        place(&mut state, 0, 1, Some(Stone::White));
+
+        assert_eq!(state.check_move(&Position(0, 0)), Err(IllegalMove::Suicide));
+
+        // Filling the last liberty of a white stone captures it instead
+        let mut state = joined(9);
+        place(&mut state, 1, 0, Some(Stone::White));
+        place(&mut state, 0, 1, Some(Stone::White));
+        place(&mut state, 2, 0, Some(Stone::Black));
+        place(&mut state, 1, 1, Some(Stone::Black));
+
+        assert_eq!(state.check_move(&Position(0, 0)), Ok(()));
+    }
+
+    /// Centre of the intersection at column `x`, row `y`.
+    fn intersection(geometry: &Geometry, x: usize, y: usize) -> (f64, f64) {
+        (geometry.inner_begin + x as f64 * geometry.line_space,
+         geometry.inner_begin + y as f64 * geometry.line_space)
+    }
+
+    #[test]
+    fn geometry_maps_intersections_back_to_positions() {
+        let geometry = Geometry::new(900_f64, 19);
+
+        for &(x, y) in &[(0, 0), (3, 15), (18, 18)] {
+            let (p_x, p_y) = intersection(&geometry, x, y);
+
+            assert_eq!(geometry.piece_position(p_x as i32, p_y as i32), Some(Position(x as u32, y as u32)));
+        }
+    }
+
+    #[test]
+    fn geometry_ignores_points_off_the_board() {
+        let geometry = Geometry::new(900_f64, 19);
+
+        assert_eq!(geometry.piece_position(0, 0), None);
+        assert_eq!(geometry.piece_position(450, 899), None);
+    }
+
+    #[test]
+    fn view_hit_testing_follows_zoom_and_pan() {
+        let geometry = Geometry::new(900_f64, 19);
+        let mut view = View::default();
+
+        view.zoom_at(3_f64, 200_f64, 700_f64, 900_f64);
+        view.pan(-50_f64, 20_f64, 900_f64);
+
+        let (p_x, p_y) = intersection(&geometry, 5, 14);
+        let (s_x, s_y) = (p_x * view.zoom() + view.offset_x, p_y * view.zoom() + view.offset_y);
+        let (b_x, b_y) = view.to_board(s_x, s_y);
+
+        assert_eq!(geometry.piece_position(b_x.round() as i32, b_y.round() as i32), Some(Position(5, 14)));
+    }
+
+    #[test]
+    fn view_stays_on_the_board() {
+        let mut view = View::default();
+
+        view.pan(100_f64, 100_f64, 900_f64);
+        assert!(view.is_identity());
+
+        view.zoom_at(10_f64, 0_f64, 0_f64, 900_f64);
+        assert_eq!(view.zoom(), MAX_ZOOM);
+
+        view.pan(-10_000_f64, 10_000_f64, 900_f64);
+        assert_eq!((view.offset_x, view.offset_y), (900_f64 * (1_f64 - MAX_ZOOM), 0_f64));
+
+        view.zoom_at(0.01_f64, 450_f64, 450_f64, 900_f64);
+        assert!(view.is_identity());
+    }
+
+    #[test]
+    fn orientation_round_trips_every_intersection() {
+        let mut orientations = Vec::new();
+
+        for mirrored in &[false, true] {
+            for quarter_turns in 0..4 {
+                orientations.push(Orientation { quarter_turns, mirrored: *mirrored });
+            }
+        }
+
+        for orientation in orientations {
+            for y in 0..5 {
+                for x in 0..5 {
+                    let (v_x, v_y) = orientation.to_view(x, y, 5);
+                    assert_eq!(orientation.to_board(v_x, v_y, 5), (x, y), "{:?}", orientation);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn orientation_turns_and_flips_as_seen() {
+        let mut orientation = Orientation::default();
+
+        orientation.rotate_clockwise();
+        assert_eq!(orientation.to_view(0, 0, 9), (8, 0));
+        assert_eq!(orientation.column_label(0, 9), "9");
+        assert_eq!(orientation.row_label(0, 9), "A");
+
+        // Flipping the turned board mirrors what is on screen, not the board.
+        orientation.flip_horizontal();
+        assert_eq!(orientation.to_view(0, 0, 9), (0, 0));
+        assert_eq!(orientation.to_view(8, 0, 9), (0, 8));
+
+        orientation.flip_vertical();
+        assert_eq!(orientation.to_view(0, 0, 9), (0, 8));
+
+        let mut orientation = Orientation::default();
+        orientation.flip_vertical();
+        orientation.flip_vertical();
+        assert_eq!(orientation, Orientation::default());
+    }
+
+    #[test]
+    fn parse_coordinate_uses_board_lettering() {
+        assert_eq!(parse_coordinate("D4", 19), Some((3, 3)));
+        assert_eq!(parse_coordinate("q16", 19), Some((16, 15)));
+        assert_eq!(parse_coordinate("A1", 9), Some((0, 0)));
+        assert_eq!(parse_coordinate("J9", 9), None);
+        assert_eq!(parse_coordinate("D0", 19), None);
+        assert_eq!(parse_coordinate("D20", 19), None);
+        assert_eq!(parse_coordinate("4D", 19), None);
+        assert_eq!(parse_coordinate(&coordinate_label(7, 12), 19), Some((7, 12)));
+    }
+
+    #[test]
+    fn column_labels_match_the_board() {
+        assert_eq!(column_label(0), "A");
+        assert_eq!(column_label(8), "I");
+        assert_eq!(column_label(18), "S");
+    }
+}
@@ -0,0 +1,640 @@
+//! Everything the client says to the user, in each language it speaks.
+//! Every language matches on every message, so a message cannot be added
+//! without a translation.
+
+use rengo_common::{
+    logic::Stone,
+    networking::{
+        GamePhase,
+        LoginError,
+        RoomCreateError,
+    },
+};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Locale {
+    #[default]
+    English,
+    German,
+}
+
+/// A message to show, with whatever it needs filled in.
+#[derive(Clone, Copy, Debug)]
+pub enum Message<'a> {
+    ServerUnreachable,
+    LoginError(&'a LoginError),
+    RoomCreateError(&'a RoomCreateError),
+    Language,
+    Spectate,
+    Rooms,
+    Room,
+    Board,
+    Players,
+    Phase,
+    GamePhase(GamePhase),
+    NoRooms,
+    Join,
+    Watch,
+    CreateRoom,
+    RoomName,
+    BoardLength,
+    RoomCapacity,
+    InvalidCapacity,
+    InvalidDimensions,
+    MissingRoomName,
+    Create,
+    Quit,
+    Close,
+    AlreadyLoggedIn,
+    AlreadyPlaying { username: &'a str, room: &'a str },
+    UseThisTab,
+    PlayingElsewhere,
+    PlayingElsewhereDescription { username: &'a str, room: &'a str },
+    SessionMoved,
+    SessionMovedDescription,
+    RoomTitle(&'a str),
+    Spectators,
+    StoneColour(Stone),
+    BoardDescription,
+    Confirm,
+    ResetView,
+    BoardOrientation,
+    Rotate,
+    RotateDescription,
+    FlipHorizontal,
+    FlipHorizontalDescription,
+    FlipVertical,
+    FlipVerticalDescription,
+    ConfirmMoves,
+    Sound,
+    Volume,
+    YourTurn,
+    YourTurnIn(&'a str),
+    YourTurnTitle(&'a str),
+    Turn(&'a str),
+    Passes(&'a str),
+    Played { player: &'a str, stone: Stone, coordinate: &'a str, captures: usize },
+    Joined(&'a str, Stone),
+    Left(&'a str),
+    Spectating(&'a str),
+    StoppedSpectating(&'a str),
+    StoneName(Stone),
+    Empty,
+    SpectatorChannel,
+    HelpPass,
+    HelpResign,
+    HelpWho,
+    HelpClear,
+    HelpMe,
+    HelpHelp,
+    CommandNeedsText(&'a str),
+    UnknownCommand(&'a str),
+    Player { username: &'a str, stone: Stone, to_play: bool },
+    WhoPlayers(&'a str),
+    WhoSpectators(&'a str),
+}
+
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::English, Locale::German];
+
+    /// Language tag, as used by browsers and the `lang` attribute.
+    pub fn tag(self) -> &'static str {
+        match self {
+            Locale::English => "en",
+            Locale::German => "de",
+        }
+    }
+
+    /// Name of the language in that language, for picking it.
+    pub fn name(self) -> &'static str {
+        match self {
+            Locale::English => "English",
+            Locale::German => "Deutsch",
+        }
+    }
+
+    /// Locale for a language tag such as "de-AT", going by the language
+    /// alone.
+    pub fn from_tag(tag: &str) -> Option<Locale> {
+        let language = tag.split(&['-', '_'][..])
+            .next()?;
+
+        Locale::ALL
+            .iter()
+            .copied()
+            .find(|locale| locale.tag().eq_ignore_ascii_case(language))
+    }
+
+    /// The first locale spoken out of `tags`, in order of preference, or
+    /// English if there is none.
+    pub fn negotiate<'a>(tags: impl IntoIterator<Item = &'a str>) -> Locale {
+        tags.into_iter()
+            .find_map(Locale::from_tag)
+            .unwrap_or_default()
+    }
+
+    pub fn text(self, message: Message) -> String {
+        match self {
+            Locale::English => english(message),
+            Locale::German => german(message),
+        }
+    }
+}
+
+fn english(message: Message) -> String {
+    match message {
+        Message::ServerUnreachable =>
+            "Could not join server: Unable to connect to server".into(),
+        Message::LoginError(error) => match error {
+            LoginError::RoomFull =>
+                "Could not join room: Room is full".into(),
+            LoginError::UsernameTaken =>
+                "Invalid username: Username is taken".into(),
+            LoginError::RoomNameTooLong =>
+                "Room name too long: Max room name is 16 characters".into(),
+            LoginError::UsernameTooLong =>
+                "Username too long: Max length is 16 characters".into(),
+            LoginError::RoomDoesNotExist(room) =>
+                format!(r#"The room "{}" does not exist yet. Please create it below."#, room),
+            LoginError::SessionExpired =>
+                "Your seat in this room has expired".into(),
+        },
+        Message::RoomCreateError(error) => match error {
+            RoomCreateError::RoomNameTooLong =>
+                "Room name too long: Max room name is 16 characters".into(),
+            RoomCreateError::RoomNameTaken =>
+                "Room name taken: Please choose another name".into(),
+        },
+        Message::Language => "Language".into(),
+        Message::Spectate => "Spectate".into(),
+        Message::Rooms => "Rooms".into(),
+        Message::Room => "Room".into(),
+        Message::Board => "Board".into(),
+        Message::Players => "Players".into(),
+        Message::Phase => "Phase".into(),
+        Message::GamePhase(phase) => match phase {
+            GamePhase::Waiting => "Waiting".into(),
+            GamePhase::Playing => "Playing".into(),
+            GamePhase::Finished => "Finished".into(),
+        },
+        Message::NoRooms => "No rooms yet. Enter a room name above to create one.".into(),
+        Message::Join => "Join".into(),
+        Message::Watch => "Watch".into(),
+        Message::CreateRoom => "Create The Room".into(),
+        Message::RoomName => "Room Name".into(),
+        Message::BoardLength => "Board Length".into(),
+        Message::RoomCapacity => "Room Capacity".into(),
+        Message::InvalidCapacity => r#"Invalid Capacity. Enter a positive integer such as "4"."#.into(),
+        Message::InvalidDimensions => r#"Invalid Dimensions. Enter a positive integer such as "9"."#.into(),
+        Message::MissingRoomName => "Invalid room name: Enter a room name".into(),
+        Message::Create => "Create".into(),
+        Message::Quit => "Quit".into(),
+        Message::Close => "Close".into(),
+        Message::AlreadyLoggedIn => "Already Logged In".into(),
+        Message::AlreadyPlaying { username, room } =>
+            format!(r#""{}" is already playing in room "{}" from another tab or window. You can move the session to this tab, which disconnects the other one."#,
+                    username, room),
+        Message::UseThisTab => "Use This Tab".into(),
+        Message::PlayingElsewhere => "Playing Elsewhere".into(),
+        Message::PlayingElsewhereDescription { username, room } =>
+            format!(r#"No other tab in this browser is playing as "{}" in room "{}", so the session is open on another device. Quit the game there, then log in again."#,
+                    username, room),
+        Message::SessionMoved => "Session Moved".into(),
+        Message::SessionMovedDescription =>
+            "This game was opened in another tab, so this tab has been disconnected.".into(),
+        Message::RoomTitle(room) => format!("Room {}", room),
+        Message::Spectators => "Spectators".into(),
+        Message::StoneColour(stone) => match stone {
+            Stone::Black => "Black".into(),
+            Stone::White => "White".into(),
+        },
+        Message::BoardDescription =>
+            "Board. Arrow keys move the cursor, Enter places a stone, or type a coordinate such as D4. Shift+Enter passes.".into(),
+        Message::Confirm => "Confirm".into(),
+        Message::ResetView => "Reset view".into(),
+        Message::BoardOrientation => "Board orientation".into(),
+        Message::Rotate => "Rotate".into(),
+        Message::RotateDescription => "Rotate the board clockwise".into(),
+        Message::FlipHorizontal => "Flip \u{2194}".into(),
+        Message::FlipHorizontalDescription => "Flip the board left to right".into(),
+        Message::FlipVertical => "Flip \u{2195}".into(),
+        Message::FlipVerticalDescription => "Flip the board top to bottom".into(),
+        Message::ConfirmMoves => "Confirm moves".into(),
+        Message::Sound => "Sound".into(),
+        Message::Volume => "Volume".into(),
+        Message::YourTurn => "Your turn".into(),
+        Message::YourTurnIn(room) => format!("It is your turn in room {}.", room),
+        Message::YourTurnTitle(title) => format!("\u{25cf} Your turn - {}", title),
+        Message::Turn(player) => format!("{}'s turn", player),
+        Message::Passes(player) => format!("{} passes", player),
+        Message::Played { player, stone, coordinate, captures } => {
+            let stone = english(Message::StoneName(stone));
+
+            match captures {
+                0 => format!("{}, {}, {}", player, stone, coordinate),
+                _ => format!("{}, {}, {}, captures {}", player, stone, coordinate, captures),
+            }
+        }
+        Message::Joined(player, stone) => format!("{} joined, {}", player, english(Message::StoneName(stone))),
+        Message::Left(player) => format!("{} left", player),
+        Message::Spectating(spectator) => format!("{} is spectating", spectator),
+        Message::StoppedSpectating(spectator) => format!("{} stopped spectating", spectator),
+        Message::StoneName(stone) => match stone {
+            Stone::Black => "black".into(),
+            Stone::White => "white".into(),
+        },
+        Message::Empty => "empty".into(),
+        Message::SpectatorChannel => "spectators".into(),
+        Message::HelpPass => "pass your turn".into(),
+        Message::HelpResign => "resign the game".into(),
+        Message::HelpWho => "list the players and spectators".into(),
+        Message::HelpClear => "clear the chat".into(),
+        Message::HelpMe => "say what you are doing".into(),
+        Message::HelpHelp => "show this list".into(),
+        Message::CommandNeedsText(command) =>
+            format!("{} needs some text after it, e.g. {} waves.", command, command),
+        Message::UnknownCommand(command) =>
+            format!("Unknown command {}. Type /help for the list, or // to start a message with a slash.", command),
+        Message::Player { username, stone, to_play } => {
+            let stone = english(Message::StoneName(stone));
+
+            if to_play {
+                format!("{} ({}, to play)", username, stone)
+            } else {
+                format!("{} ({})", username, stone)
+            }
+        }
+        Message::WhoPlayers(players) => format!("Players: {}.", players),
+        Message::WhoSpectators(spectators) => format!("Spectators: {}.", spectators),
+    }
+}
+
+fn german(message: Message) -> String {
+    match message {
+        Message::ServerUnreachable =>
+            "Verbindung fehlgeschlagen: Der Server ist nicht erreichbar".into(),
+        Message::LoginError(error) => match error {
+            LoginError::RoomFull =>
+                "Beitritt nicht möglich: Der Raum ist voll".into(),
+            LoginError::UsernameTaken =>
+                "Ungültiger Benutzername: Der Name ist schon vergeben".into(),
+            LoginError::RoomNameTooLong =>
+                "Raumname zu lang: Höchstens 16 Zeichen".into(),
+            LoginError::UsernameTooLong =>
+                "Benutzername zu lang: Höchstens 16 Zeichen".into(),
+            LoginError::RoomDoesNotExist(room) =>
+                format!("Den Raum \u{201e}{}\u{201c} gibt es noch nicht. Du kannst ihn unten erstellen.", room),
+            LoginError::SessionExpired =>
+                "Dein Platz in diesem Raum ist abgelaufen".into(),
+        },
+        Message::RoomCreateError(error) => match error {
+            RoomCreateError::RoomNameTooLong =>
+                "Raumname zu lang: Höchstens 16 Zeichen".into(),
+            RoomCreateError::RoomNameTaken =>
+                "Raumname vergeben: Bitte wähle einen anderen Namen".into(),
+        },
+        Message::Language => "Sprache".into(),
+        Message::Spectate => "Zuschauen".into(),
+        Message::Rooms => "Räume".into(),
+        Message::Room => "Raum".into(),
+        Message::Board => "Brett".into(),
+        Message::Players => "Spieler".into(),
+        Message::Phase => "Phase".into(),
+        Message::GamePhase(phase) => match phase {
+            GamePhase::Waiting => "Wartet".into(),
+            GamePhase::Playing => "Läuft".into(),
+            GamePhase::Finished => "Beendet".into(),
+        },
+        Message::NoRooms => "Noch keine Räume. Gib oben einen Raumnamen ein, um einen zu erstellen.".into(),
+        Message::Join => "Beitreten".into(),
+        Message::Watch => "Zuschauen".into(),
+        Message::CreateRoom => "Raum erstellen".into(),
+        Message::RoomName => "Raumname".into(),
+        Message::BoardLength => "Brettgröße".into(),
+        Message::RoomCapacity => "Anzahl der Plätze".into(),
+        Message::InvalidCapacity =>
+            "Ungültige Anzahl. Gib eine positive ganze Zahl wie \u{201e}4\u{201c} ein.".into(),
+        Message::InvalidDimensions =>
+            "Ungültige Größe. Gib eine positive ganze Zahl wie \u{201e}9\u{201c} ein.".into(),
+        Message::MissingRoomName => "Ungültiger Raumname: Gib einen Raumnamen ein".into(),
+        Message::Create => "Erstellen".into(),
+        Message::Quit => "Verlassen".into(),
+        Message::Close => "Schließen".into(),
+        Message::AlreadyLoggedIn => "Bereits angemeldet".into(),
+        Message::AlreadyPlaying { username, room } =>
+            format!("\u{201e}{}\u{201c} spielt schon in Raum \u{201e}{}\u{201c} in einem anderen Tab oder Fenster. Du kannst die Sitzung in diesen Tab holen, wodurch der andere getrennt wird.",
+                    username, room),
+        Message::UseThisTab => "Diesen Tab verwenden".into(),
+        Message::PlayingElsewhere => "Woanders angemeldet".into(),
+        Message::PlayingElsewhereDescription { username, room } =>
+            format!("Kein anderer Tab in diesem Browser spielt als \u{201e}{}\u{201c} in Raum \u{201e}{}\u{201c}, die Sitzung ist also auf einem anderen Gerät offen. Beende das Spiel dort und melde dich dann erneut an.",
+                    username, room),
+        Message::SessionMoved => "Sitzung verschoben".into(),
+        Message::SessionMovedDescription =>
+            "Dieses Spiel wurde in einem anderen Tab geöffnet, deshalb wurde dieser Tab getrennt.".into(),
+        Message::RoomTitle(room) => format!("Raum {}", room),
+        Message::Spectators => "Zuschauer".into(),
+        Message::StoneColour(stone) => match stone {
+            Stone::Black => "Schwarz".into(),
+            Stone::White => "Weiß".into(),
+        },
+        Message::BoardDescription =>
+            "Brett. Die Pfeiltasten bewegen den Cursor, Enter setzt einen Stein, oder tippe eine Koordinate wie D4. Umschalt+Enter passt.".into(),
+        Message::Confirm => "Bestätigen".into(),
+        Message::ResetView => "Ansicht zurücksetzen".into(),
+        Message::BoardOrientation => "Ausrichtung des Bretts".into(),
+        Message::Rotate => "Drehen".into(),
+        Message::RotateDescription => "Das Brett im Uhrzeigersinn drehen".into(),
+        Message::FlipHorizontal => "Spiegeln \u{2194}".into(),
+        Message::FlipHorizontalDescription => "Das Brett von links nach rechts spiegeln".into(),
+        Message::FlipVertical => "Spiegeln \u{2195}".into(),
+        Message::FlipVerticalDescription => "Das Brett von oben nach unten spiegeln".into(),
+        Message::ConfirmMoves => "Züge bestätigen".into(),
+        Message::Sound => "Ton".into(),
+        Message::Volume => "Lautstärke".into(),
+        Message::YourTurn => "Du bist am Zug".into(),
+        Message::YourTurnIn(room) => format!("Du bist in Raum {} am Zug.", room),
+        Message::YourTurnTitle(title) => format!("\u{25cf} Du bist am Zug - {}", title),
+        Message::Turn(player) => format!("{} ist am Zug", player),
+        Message::Passes(player) => format!("{} passt", player),
+        Message::Played { player, stone, coordinate, captures } => {
+            let stone = german(Message::StoneName(stone));
+
+            match captures {
+                0 => format!("{}, {}, {}", player, stone, coordinate),
+                _ => format!("{}, {}, {}, schlägt {}", player, stone, coordinate, captures),
+            }
+        }
+        Message::Joined(player, stone) => format!("{} ist beigetreten, {}", player, german(Message::StoneName(stone))),
+        Message::Left(player) => format!("{} ist gegangen", player),
+        Message::Spectating(spectator) => format!("{} schaut zu", spectator),
+        Message::StoppedSpectating(spectator) => format!("{} schaut nicht mehr zu", spectator),
+        Message::StoneName(stone) => match stone {
+            Stone::Black => "schwarz".into(),
+            Stone::White => "weiß".into(),
+        },
+        Message::Empty => "leer".into(),
+        Message::SpectatorChannel => "Zuschauer".into(),
+        Message::HelpPass => "den Zug passen".into(),
+        Message::HelpResign => "das Spiel aufgeben".into(),
+        Message::HelpWho => "Spieler und Zuschauer auflisten".into(),
+        Message::HelpClear => "den Chat leeren".into(),
+        Message::HelpMe => "sagen, was du gerade tust".into(),
+        Message::HelpHelp => "diese Liste anzeigen".into(),
+        Message::CommandNeedsText(command) =>
+            format!("Nach {} fehlt noch Text, z.\u{a0}B. {} winkt.", command, command),
+        Message::UnknownCommand(command) =>
+            format!("Unbekannter Befehl {}. Tippe /help für die Liste, oder // um eine Nachricht mit einem Schrägstrich zu beginnen.", command),
+        Message::Player { username, stone, to_play } => {
+            let stone = german(Message::StoneName(stone));
+
+            if to_play {
+                format!("{} ({}, am Zug)", username, stone)
+            } else {
+                format!("{} ({})", username, stone)
+            }
+        }
+        Message::WhoPlayers(players) => format!("Spieler: {}.", players),
+        Message::WhoSpectators(spectators) => format!("Zuschauer: {}.", spectators),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static LOGIN_ERRORS: [LoginError; 6] = [
+        LoginError::RoomFull,
+        LoginError::UsernameTaken,
+        LoginError::RoomNameTooLong,
+        LoginError::UsernameTooLong,
+        LoginError::RoomDoesNotExist(String::new()),
+        LoginError::SessionExpired,
+    ];
+
+    static ROOM_CREATE_ERRORS: [RoomCreateError; 2] = [
+        RoomCreateError::RoomNameTooLong,
+        RoomCreateError::RoomNameTaken,
+    ];
+
+    /// Every message, with sample arguments where it takes any.
+    fn catalogue() -> Vec<Message<'static>> {
+        let mut messages = vec![
+            Message::ServerUnreachable,
+            Message::Language,
+            Message::Spectate,
+            Message::Rooms,
+            Message::Room,
+            Message::Board,
+            Message::Players,
+            Message::Phase,
+            Message::GamePhase(GamePhase::Waiting),
+            Message::GamePhase(GamePhase::Playing),
+            Message::GamePhase(GamePhase::Finished),
+            Message::NoRooms,
+            Message::Join,
+            Message::Watch,
+            Message::CreateRoom,
+            Message::RoomName,
+            Message::BoardLength,
+            Message::RoomCapacity,
+            Message::InvalidCapacity,
+            Message::InvalidDimensions,
+            Message::MissingRoomName,
+            Message::Create,
+            Message::Quit,
+            Message::Close,
+            Message::AlreadyLoggedIn,
+            Message::AlreadyPlaying { username: "ann", room: "go" },
+            Message::UseThisTab,
+            Message::PlayingElsewhere,
+            Message::PlayingElsewhereDescription { username: "ann", room: "go" },
+            Message::SessionMoved,
+            Message::SessionMovedDescription,
+            Message::RoomTitle("go"),
+            Message::Spectators,
+            Message::StoneColour(Stone::Black),
+            Message::StoneColour(Stone::White),
+            Message::BoardDescription,
+            Message::Confirm,
+            Message::ResetView,
+            Message::BoardOrientation,
+            Message::Rotate,
+            Message::RotateDescription,
+            Message::FlipHorizontal,
+            Message::FlipHorizontalDescription,
+            Message::FlipVertical,
+            Message::FlipVerticalDescription,
+            Message::ConfirmMoves,
+            Message::Sound,
+            Message::Volume,
+            Message::YourTurn,
+            Message::YourTurnIn("go"),
+            Message::YourTurnTitle("Rengo"),
+            Message::Turn("ann"),
+            Message::Passes("ann"),
+            Message::Played { player: "ann", stone: Stone::Black, coordinate: "D4", captures: 0 },
+            Message::Played { player: "ann", stone: Stone::White, coordinate: "D4", captures: 2 },
+            Message::Joined("ann", Stone::Black),
+            Message::Left("ann"),
+            Message::Spectating("bob"),
+            Message::StoppedSpectating("bob"),
+            Message::StoneName(Stone::Black),
+            Message::StoneName(Stone::White),
+            Message::Empty,
+            Message::SpectatorChannel,
+            Message::HelpPass,
+            Message::HelpResign,
+            Message::HelpWho,
+            Message::HelpClear,
+            Message::HelpMe,
+            Message::HelpHelp,
+            Message::CommandNeedsText("/me"),
+            Message::UnknownCommand("/dance"),
+            Message::Player { username: "ann", stone: Stone::Black, to_play: true },
+            Message::Player { username: "ann", stone: Stone::White, to_play: false },
+            Message::WhoPlayers("ann"),
+            Message::WhoSpectators("bob"),
+        ];
+
+        messages.extend(LOGIN_ERRORS.iter().map(Message::LoginError));
+        messages.extend(ROOM_CREATE_ERRORS.iter().map(Message::RoomCreateError));
+
+        messages
+    }
+
+    /// Number of entries `entry` tells apart.
+    const ENTRIES: usize = 81;
+
+    /// Which catalogue entry `message` is. There is no wildcard arm, so a new
+    /// message or error fails to compile until it is numbered here, and the
+    /// test fails until the catalogue has it.
+    fn entry(message: Message) -> usize {
+        match message {
+            Message::ServerUnreachable => 0,
+            Message::LoginError(LoginError::RoomFull) => 1,
+            Message::LoginError(LoginError::UsernameTaken) => 2,
+            Message::LoginError(LoginError::RoomNameTooLong) => 3,
+            Message::LoginError(LoginError::UsernameTooLong) => 4,
+            Message::LoginError(LoginError::RoomDoesNotExist(_)) => 5,
+            Message::LoginError(LoginError::SessionExpired) => 6,
+            Message::RoomCreateError(RoomCreateError::RoomNameTooLong) => 7,
+            Message::RoomCreateError(RoomCreateError::RoomNameTaken) => 8,
+            Message::Language => 9,
+            Message::Spectate => 10,
+            Message::Rooms => 11,
+            Message::Room => 12,
+            Message::Board => 13,
+            Message::Players => 14,
+            Message::Phase => 15,
+            Message::GamePhase(GamePhase::Waiting) => 16,
+            Message::GamePhase(GamePhase::Playing) => 17,
+            Message::GamePhase(GamePhase::Finished) => 18,
+            Message::NoRooms => 19,
+            Message::Join => 20,
+            Message::Watch => 21,
+            Message::CreateRoom => 22,
+            Message::RoomName => 23,
+            Message::BoardLength => 24,
+            Message::RoomCapacity => 25,
+            Message::InvalidCapacity => 26,
+            Message::InvalidDimensions => 27,
+            Message::MissingRoomName => 28,
+            Message::Create => 29,
+            Message::Quit => 30,
+            Message::Close => 31,
+            Message::AlreadyLoggedIn => 32,
+            Message::AlreadyPlaying { .. } => 33,
+            Message::UseThisTab => 34,
+            Message::PlayingElsewhere => 35,
+            Message::PlayingElsewhereDescription { .. } => 36,
+            Message::SessionMoved => 37,
+            Message::SessionMovedDescription => 38,
+            Message::RoomTitle(..) => 39,
+            Message::Spectators => 40,
+            Message::StoneColour(Stone::Black) => 41,
+            Message::StoneColour(Stone::White) => 42,
+            Message::BoardDescription => 43,
+            Message::Confirm => 44,
+            Message::ResetView => 45,
+            Message::BoardOrientation => 46,
+            Message::Rotate => 47,
+            Message::RotateDescription => 48,
+            Message::FlipHorizontal => 49,
+            Message::FlipHorizontalDescription => 50,
+            Message::FlipVertical => 51,
+            Message::FlipVerticalDescription => 52,
+            Message::ConfirmMoves => 53,
+            Message::Sound => 54,
+            Message::Volume => 55,
+            Message::YourTurn => 56,
+            Message::YourTurnIn(..) => 57,
+            Message::YourTurnTitle(..) => 58,
+            Message::Turn(..) => 59,
+            Message::Passes(..) => 60,
+            Message::Played { .. } => 61,
+            Message::Joined(..) => 62,
+            Message::Left(..) => 63,
+            Message::Spectating(..) => 64,
+            Message::StoppedSpectating(..) => 65,
+            Message::StoneName(Stone::Black) => 66,
+            Message::StoneName(Stone::White) => 67,
+            Message::Empty => 68,
+            Message::SpectatorChannel => 69,
+            Message::HelpPass => 70,
+            Message::HelpResign => 71,
+            Message::HelpWho => 72,
+            Message::HelpClear => 73,
+            Message::HelpMe => 74,
+            Message::HelpHelp => 75,
+            Message::CommandNeedsText(..) => 76,
+            Message::UnknownCommand(..) => 77,
+            Message::Player { .. } => 78,
+            Message::WhoPlayers(..) => 79,
+            Message::WhoSpectators(..) => 80,
+        }
+    }
+
+    /// Messages that are spelled the same in every language.
+    fn is_loanword(message: Message) -> bool {
+        matches!(message, Message::Phase)
+    }
+
+    #[test]
+    fn every_message_is_translated() {
+        let mut entries: Vec<usize> = catalogue().into_iter().map(entry).collect();
+        entries.sort_unstable();
+        entries.dedup();
+        assert_eq!(entries, (0..ENTRIES).collect::<Vec<_>>(), "the catalogue misses messages");
+
+        for message in catalogue() {
+            let english = Locale::English.text(message);
+            assert!(! english.is_empty(), "{:?} is empty", message);
+
+            for locale in Locale::ALL.iter().filter(|locale| **locale != Locale::English) {
+                let text = locale.text(message);
+
+                assert!(! text.is_empty(), "{:?} is empty in {:?}", message, locale);
+
+                if ! is_loanword(message) {
+                    assert_ne!(text, english, "{:?} is untranslated in {:?}", message, locale);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn negotiate_takes_the_first_spoken_language() {
+        assert_eq!(Locale::negotiate(vec!["fr-FR", "de-AT", "en"]), Locale::German);
+        assert_eq!(Locale::negotiate(vec!["EN-gb", "de"]), Locale::English);
+        assert_eq!(Locale::negotiate(vec!["fr", "deu"]), Locale::English);
+        assert_eq!(Locale::negotiate(vec![]), Locale::English);
+    }
+
+    #[test]
+    fn tags_round_trip() {
+        for locale in Locale::ALL.iter() {
+            assert_eq!(Locale::from_tag(locale.tag()), Some(*locale));
+        }
+    }
+}
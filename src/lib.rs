@@ -1,7 +1,48 @@
+mod audio;
+mod chat;
+mod dom;
+mod game;
+mod i18n;
+mod modal;
+mod players;
+
 use {
+    audio::{
+        Audio,
+        Sound,
+    },
+    chat::{
+        Command,
+        CommandError,
+    },
+    game::{
+        State,
+        Geometry,
+        View,
+        Orientation,
+        column_label,
+        coordinate_label,
+        parse_coordinate,
+    },
+    i18n::{
+        Locale,
+        Message,
+    },
+    modal::{
+        Modal,
+        OpenModal,
+    },
+    players::{
+        Highlight,
+        PlayerList,
+    },
     std::{
-        sync::Mutex,
         cell::RefCell,
+        collections::VecDeque,
+        rc::{
+            Rc,
+            Weak,
+        },
     },
     rengo_common::networking::*,
     rengo_common::logic::{
@@ -16,6 +57,7 @@ use {
     },
     web_sys::{
         Blob,
+        BroadcastChannel,
         FileReader,
         HtmlElement,
         HtmlCanvasElement,
@@ -23,8 +65,13 @@ use {
         KeyboardEvent,
         CanvasRenderingContext2d,
         HtmlInputElement,
+        HtmlSelectElement,
+        Notification,
+        NotificationOptions,
+        NotificationPermission,
         WebSocket,
-        MouseEvent,
+        PointerEvent,
+        WheelEvent,
         MessageEvent,
         ProgressEvent,
     },
@@ -34,36 +81,435 @@ macro_rules! console_log {
     ($($t:tt)*) => (web_sys::console::log_1(&format!($($t)*).into()))
 }
 
+/// Device preference for staging moves before sending them.
+const CONFIRM_MOVES_KEY: &str = "rengo-confirm-moves";
+
+/// Device preference for the language of the interface.
+const LOCALE_KEY: &str = "rengo-locale";
+
+/// Upper bound on the width of an offscreen layer, in device pixels.
+const MAX_LAYER_PIXELS: f64 = 4096_f64;
+
 type JsResult<T> = Result<T, JsValue>;
 type JsError = JsResult<()>;
 type JsClosureNone = Closure<dyn FnMut() -> JsError>;
 type JsClosure<T> = Closure<dyn FnMut(T) -> JsError>;
 
-unsafe impl Send for Game {}
+/// Everything the browser can ask the client to do. Listeners never touch
+/// `App` directly; they turn DOM, socket and timer callbacks into events and
+/// hand them to a `Dispatcher`.
+#[derive(Clone)]
+enum Event {
+    LoginSubmit,
+    SpectateSubmit,
+    LobbyJoin(String, bool),
+    WsOpen,
+    WsError,
+    WsClose,
+    WsMessage(Vec<u8>),
+    RoomCreateSubmit,
+    StatusQuit,
+    ModalClose,
+    TakeoverAccept,
+    TakeoverTimeout,
+    TabMessage(String, (String, String, String)),
+    MouseDown(i32, i32),
+    MouseMove(i32, i32),
+    MouseClick(i32, i32),
+    MouseOut,
+    TouchDown(i32, i32, i32),
+    TouchMove(i32, i32, i32),
+    TouchUp(i32, i32, i32),
+    TouchCancel(i32),
+    Zoom(f64, i32, i32),
+    ResetView,
+    RotateView,
+    FlipHorizontal,
+    FlipVertical,
+    ConfirmMovesToggle,
+    LocaleChange,
+    SoundToggle,
+    VolumeChange,
+    TitleFlash,
+    WindowFocus,
+    MarkerShow(usize, usize, bool),
+    MarkerHide,
+    MarkerTimeout,
+    ConfirmPlace,
+    CursorMove(i32, i32),
+    CursorPlace,
+    CursorType(char),
+    Resize,
+    BoardImageLoad,
+    Pass,
+    Quit,
+    ChatSubmit,
+    AnimationFrame,
+    VisibilityChange,
+}
+
+struct AppCell {
+    app: RefCell<App>,
+    queue: RefCell<VecDeque<Event>>,
+}
+
+/// Handle held by event listeners to feed events into the application.
+#[derive(Clone)]
+struct Dispatcher(Rc<AppCell>);
+
+impl Dispatcher {
+    fn dispatch(&self, event: Event) {
+        self.0.queue
+            .borrow_mut()
+            .push_back(event);
+
+        // An event raised while a handler is running stays queued; the
+        // dispatch further up the stack picks it up once the handler returns.
+        let mut app = match self.0.app.try_borrow_mut() {
+            Ok(app) => app,
+            Err(_) => return,
+        };
+
+        loop {
+            let event = self.0.queue
+                .borrow_mut()
+                .pop_front();
+
+            match event {
+                Some(event) => if let Err(e) = app.handle(event) {
+                    web_sys::console::error_1(&e);
+                },
+                None => break,
+            }
+        }
+    }
+
+    fn callback(&self, event: Event) -> JsClosureNone {
+        let dispatcher = self.clone();
 
-struct Game {
+        Closure::wrap(Box::new(move || {
+            dispatcher.dispatch(event.clone());
+            Ok::<(), JsValue>(())
+        }) as Box<dyn FnMut() -> JsError>)
+    }
+}
+
+struct App {
+    this: Weak<AppCell>,
     ws: Option<WebSocket>,
-    room: Option<Room>,
-    inner_begin: Option<f64>,
-    inner_size: Option<f64>,
-    line_space: Option<f64>,
+    state: State,
+    geometry: Option<Geometry>,
+    tab_channel: Option<BroadcastChannel>,
+    takeover_pending: bool,
+    release_pending: bool,
+    layers: Option<Layers>,
+    render_dirty: bool,
+    render_frame: Option<i32>,
+    preview: Option<Position<i32>>,
+    touching: bool,
+    touches: Vec<(i32, Position<i32>)>,
+    pinching: bool,
+    drag: Option<Drag>,
+    view: View,
+    orientation: Orientation,
+    staged: Option<Position<u32>>,
+    confirm_moves: bool,
+    cursor: Option<(usize, usize)>,
+    typed: String,
+    marker: Option<(usize, usize)>,
+    marker_timeout: Option<i32>,
+    pending_move: Option<PendingMove>,
+    audio: Audio,
+    capture_sounded: bool,
+    turn_alert: Option<TurnAlert>,
+    players: PlayerList,
+    spectators: PlayerList,
+    locale: Locale,
+    modal: Option<OpenModal>,
+    board_size: u32,
+}
+
+/// Title flashing while it is the player's turn in a tab they are not
+/// looking at. The interval is cleared and the title restored on drop.
+struct TurnAlert {
+    interval: i32,
+    title: String,
+    _on_tick: JsClosureNone,
+}
+
+impl Drop for TurnAlert {
+    fn drop(&mut self) {
+        let window = web_sys::window()
+            .unwrap();
+
+        window.clear_interval_with_handle(self.interval);
+
+        window.document()
+            .unwrap()
+            .set_title(&self.title);
+    }
+}
+
+/// A mouse button held down on the board. It only pans once the pointer has
+/// moved a few pixels, so that an ordinary click still places a stone.
+struct Drag {
+    start: Position<i32>,
+    last: Position<i32>,
+    panning: bool,
 }
 
-impl Game {
-    fn login() -> JsError {
-        Game::on_button_login_submit()?;
-        Game::on_window_resize()?;
-        Game::set_mouse_move()?;
-        Game::set_mouse_click()?;
-        Game::set_mouse_out()?;
-        Game::set_pass_button()?;
-        Game::set_quit_button()?;
-        Game::set_message_button()?;
-        Game::set_enter_key()?;
+/// A stone that has been placed but not announced yet, so that the captures
+/// the server reports right after it can be announced along with it.
+struct PendingMove {
+    player: String,
+    stone: Stone,
+    position: Position<u8>,
+    captures: usize,
+}
+
+/// Offscreen canvases the board is composited from. The board layer only
+/// changes with the canvas size, the stones layer with the board contents,
+/// and the hover ghost and keyboard cursor are drawn straight onto the
+/// visible canvas.
+struct Layers {
+    board: HtmlCanvasElement,
+    stones: HtmlCanvasElement,
+    board_key: Option<(u32, usize, f64, Orientation)>,
+    stones_drawn: Vec<Vec<Option<Stone>>>,
+    hover: Option<(usize, usize)>,
+    hover_staged: bool,
+    cursor: Option<(usize, usize)>,
+    marker: Option<(usize, usize)>,
+    view: View,
+}
+
+impl App {
+    fn start() -> JsError {
+        let locale = App::locale_load();
+
+        web_sys::window()
+            .unwrap()
+            .document()
+            .unwrap()
+            .document_element()
+            .unwrap()
+            .set_attribute("lang", locale.tag())?;
+
+        let cell = Rc::new(AppCell {
+            app: RefCell::new(App {
+                this: Weak::new(),
+                ws: None,
+                state: State::default(),
+                geometry: None,
+                tab_channel: None,
+                takeover_pending: false,
+                release_pending: false,
+                layers: None,
+                render_dirty: false,
+                render_frame: None,
+                preview: None,
+                touching: false,
+                touches: Vec::new(),
+                pinching: false,
+                drag: None,
+                view: View::default(),
+                orientation: Orientation::default(),
+                staged: None,
+                confirm_moves: App::preference_load(CONFIRM_MOVES_KEY)
+                    .is_some_and(|value| value == "true"),
+                cursor: None,
+                typed: String::new(),
+                marker: None,
+                marker_timeout: None,
+                pending_move: None,
+                audio: Audio::default(),
+                capture_sounded: false,
+                turn_alert: None,
+                players: PlayerList::default(),
+                spectators: PlayerList::default(),
+                locale,
+                modal: None,
+                board_size: App::get_window_size(),
+            }),
+            queue: RefCell::new(VecDeque::new()),
+        });
+
+        cell.app
+            .borrow_mut()
+            .this = Rc::downgrade(&cell);
+
+        let dispatcher = Dispatcher(cell);
+
+        let tab_channel = App::set_tab_channel(&dispatcher)?;
+        dispatcher.0.app
+            .borrow_mut()
+            .tab_channel = tab_channel;
+
+        App::on_button_login_submit(&dispatcher)?;
+        App::set_spectate_button(&dispatcher, locale)?;
+        App::set_lobby(&dispatcher, locale)?;
+        App::set_locale_select(&dispatcher, locale)?;
+        App::set_visibility_change(&dispatcher)?;
+        App::set_window_focus(&dispatcher)?;
+        App::set_window_resize(&dispatcher)?;
+        App::set_board_image_load(&dispatcher)?;
+        App::set_pointer_input(&dispatcher)?;
+        App::set_keyboard_input(&dispatcher, locale)?;
+        App::set_confirm_button(&dispatcher, locale)?;
+        App::set_reset_view_button(&dispatcher, locale)?;
+        App::set_orientation_buttons(&dispatcher, locale)?;
+        App::set_confirm_moves_toggle(&dispatcher, locale)?;
+        App::set_sound_controls(&dispatcher, locale)?;
+        App::set_pass_button(&dispatcher)?;
+        App::set_quit_button(&dispatcher)?;
+        App::set_message_button(&dispatcher)?;
+        App::set_chat_coordinates(&dispatcher)?;
+        App::set_enter_key(&dispatcher)?;
+
+        // Listeners hold their own dispatchers from here on, keeping the
+        // application alive for the lifetime of the page.
+        Ok(())
+    }
+
+    fn dispatcher(&self) -> Dispatcher {
+        Dispatcher(self.this
+            .upgrade()
+            .unwrap())
+    }
+
+    fn handle(&mut self, event: Event) -> JsError {
+        // Browsers only let audio start from a user gesture, so take the
+        // first one that comes along.
+        if matches!(event, Event::LoginSubmit | Event::SpectateSubmit | Event::LobbyJoin(..)
+                         | Event::MouseClick(..) | Event::TouchUp(..) | Event::CursorPlace
+                         | Event::SoundToggle | Event::VolumeChange)
+        {
+            if let Err(e) = self.audio.unlock() {
+                web_sys::console::error_1(&e);
+            }
+        }
+
+        match event {
+            Event::LoginSubmit => {
+                self.state.spectating = false;
+                self.connect()?;
+            }
+            Event::SpectateSubmit => {
+                self.state.spectating = true;
+                self.connect()?;
+            }
+            Event::LobbyJoin(room_name, spectate) =>
+                self.on_lobby_room_click(&room_name, spectate)?,
+            Event::WsOpen =>
+                self.on_ws_open()?,
+            Event::WsError =>
+                self.on_ws_error()?,
+            Event::WsClose =>
+                self.on_ws_close()?,
+            Event::WsMessage(data) =>
+                self.on_ws_message(&data)?,
+            Event::RoomCreateSubmit =>
+                self.on_room_create_button_submit()?,
+            Event::StatusQuit =>
+                self.on_status_quit()?,
+            Event::ModalClose =>
+                self.modal = None,
+            Event::TakeoverAccept =>
+                self.on_takeover()?,
+            Event::TakeoverTimeout =>
+                self.on_takeover_timeout()?,
+            Event::TabMessage(kind, seat) =>
+                self.on_tab_message(&kind, seat)?,
+            Event::MouseDown(x, y) =>
+                self.on_mouse_down(x, y)?,
+            Event::MouseMove(x, y) => {
+                self.on_mouse_move(x, y)?;
+                self.request_render()?;
+            }
+            Event::MouseClick(x, y) => {
+                self.on_mouse_click(x, y)?;
+                self.request_render()?;
+            }
+            Event::MouseOut => {
+                self.on_mouse_out()?;
+                self.request_render()?;
+            }
+            Event::TouchDown(id, x, y) =>
+                self.on_touch_down(id, x, y)?,
+            Event::TouchMove(id, x, y) =>
+                self.on_touch_move(id, x, y)?,
+            Event::TouchUp(id, x, y) =>
+                self.on_touch_up(id, x, y)?,
+            Event::TouchCancel(id) =>
+                self.on_touch_cancel(id)?,
+            Event::Zoom(factor, x, y) =>
+                self.on_zoom(factor, x, y)?,
+            Event::ResetView =>
+                self.on_reset_view()?,
+            Event::RotateView => {
+                self.orientation.rotate_clockwise();
+                self.request_render()?;
+            }
+            Event::FlipHorizontal => {
+                self.orientation.flip_horizontal();
+                self.request_render()?;
+            }
+            Event::FlipVertical => {
+                self.orientation.flip_vertical();
+                self.request_render()?;
+            }
+            Event::ConfirmMovesToggle =>
+                self.on_confirm_moves_toggle()?,
+            Event::LocaleChange =>
+                App::on_locale_change()?,
+            Event::SoundToggle =>
+                self.on_sound_toggle()?,
+            Event::VolumeChange =>
+                self.on_volume_change()?,
+            Event::TitleFlash =>
+                self.on_title_flash()?,
+            Event::MarkerShow(x, y, timed) =>
+                self.on_marker_show(x, y, timed)?,
+            Event::MarkerHide => {
+                // A marker shown by a click outlives the pointer leaving.
+                if self.marker_timeout.is_none() {
+                    self.set_marker(None)?;
+                }
+            }
+            Event::MarkerTimeout => {
+                self.marker_timeout = None;
+                self.set_marker(None)?;
+            }
+            Event::WindowFocus =>
+                self.turn_alert = None,
+            Event::ConfirmPlace =>
+                self.on_confirm_place()?,
+            Event::CursorMove(dx, dy) =>
+                self.on_cursor_move(dx, dy)?,
+            Event::CursorPlace =>
+                self.on_cursor_place()?,
+            Event::CursorType(c) =>
+                self.on_cursor_type(c)?,
+            Event::Resize =>
+                self.on_window_resize()?,
+            Event::BoardImageLoad =>
+                self.on_board_image_load()?,
+            Event::Pass =>
+                self.on_pass()?,
+            Event::Quit =>
+                self.on_quit()?,
+            Event::ChatSubmit =>
+                self.on_chat_submit()?,
+            Event::AnimationFrame =>
+                self.on_animation_frame()?,
+            Event::VisibilityChange =>
+                self.on_visibility_change()?,
+        }
+
         Ok(())
     }
 
-    fn on_ws_open() -> JsError {
+    fn on_ws_open(&mut self) -> JsError {
         let document = web_sys::window()
             .unwrap()
             .document()
@@ -88,13 +534,114 @@ impl Game {
             .unwrap()
             .dyn_into::<HtmlInputElement>()?;
 
-        let login_message = ClientMessage::Login(login_username.value(), login_room.value());
-        Game::ws_send_message(&login_message)?;
+        self.ws_send_message(&ClientMessage::RoomList)?;
+
+        if ! login_room.value().is_empty() {
+            let login_message = self.login_message(login_username.value(), login_room.value());
+            self.ws_send_message(&login_message)?;
+        }
+
+        Ok(())
+    }
+
+    fn login_message(&self, username: String, room: String) -> ClientMessage {
+        if self.state.spectating {
+            ClientMessage::Spectate(username, room)
+        } else if let Some(token) = App::session_token_load(&username, &room) {
+            ClientMessage::LoginWithToken(username, room, token)
+        } else {
+            ClientMessage::Login(username, room)
+        }
+    }
+
+    /// Reads a setting kept for this device, if storage is available.
+    fn preference_load(key: &str) -> Option<String> {
+        web_sys::window()
+            .unwrap()
+            .local_storage()
+            .ok()
+            .flatten()?
+            .get_item(key)
+            .ok()
+            .flatten()
+    }
+
+    fn preference_store(key: &str, value: &str) -> JsError {
+        let storage = web_sys::window()
+            .unwrap()
+            .local_storage()?;
+
+        // Without storage the setting just lasts until the page is closed
+        if let Some(storage) = storage {
+            storage.set_item(key, value)?;
+        }
+
+        Ok(())
+    }
+
+    /// Language picked on this device, or else the first one asked for by
+    /// the browser that the client speaks.
+    fn locale_load() -> Locale {
+        if let Some(locale) = App::preference_load(LOCALE_KEY).and_then(|tag| Locale::from_tag(&tag)) {
+            return locale;
+        }
+
+        let navigator = web_sys::window()
+            .unwrap()
+            .navigator();
+
+        let languages = navigator.languages()
+            .iter()
+            .filter_map(|language| language.as_string())
+            .chain(navigator.language())
+            .collect::<Vec<String>>();
+
+        Locale::negotiate(languages.iter().map(String::as_str))
+    }
+
+    fn session_token_key(username: &str, room: &str) -> String {
+        let login_server = web_sys::window()
+            .unwrap()
+            .document()
+            .unwrap()
+            .get_element_by_id("loginServer")
+            .unwrap()
+            .dyn_into::<HtmlInputElement>()
+            .unwrap();
+
+        format!("rengo-session:{}:{}:{}", login_server.value(), room, username)
+    }
+
+    fn session_token_load(username: &str, room: &str) -> Option<String> {
+        web_sys::window()
+            .unwrap()
+            .local_storage()
+            .ok()
+            .flatten()?
+            .get_item(&App::session_token_key(username, room))
+            .ok()
+            .flatten()
+    }
+
+    fn session_token_store(username: &str, room: &str, token: Option<&str>) -> JsError {
+        let storage = web_sys::window()
+            .unwrap()
+            .local_storage()?;
+
+        // Without storage, seats simply cannot be reclaimed after a reload
+        if let Some(storage) = storage {
+            let key = App::session_token_key(username, room);
+
+            match token {
+                Some(token) => storage.set_item(&key, token)?,
+                None => storage.remove_item(&key)?,
+            }
+        }
 
         Ok(())
     }
 
-    fn on_ws_error() -> JsError {
+    fn on_ws_error(&mut self) -> JsError {
         let document = web_sys::window()
             .unwrap()
             .document()
@@ -113,7 +660,7 @@ impl Game {
                 .dyn_into::<HtmlElement>()?;
             login_server_error.set_id("loginServerError");
             login_server_error.set_class_name("invalid-feedback");
-            login_server_error.set_inner_text("Could not join server: Unable to connect to server");
+            login_server_error.set_inner_text(&self.locale.text(Message::ServerUnreachable));
 
             let login_server_form = document.get_element_by_id("loginServerForm")
                 .unwrap();
@@ -123,18 +670,15 @@ impl Game {
             login_server_error
                 .unwrap()
                 .dyn_into::<HtmlElement>()?
-                .set_inner_text("Could not join server: Unable to connect to server");
+                .set_inner_text(&self.locale.text(Message::ServerUnreachable));
         }
 
-        STATE.lock()
-            .unwrap()
-            .borrow_mut()
-            .ws = None;
+        self.ws = None;
 
         Ok(())
     }
 
-    fn on_ws_close() -> JsError {
+    fn on_ws_close(&mut self) -> JsError {
         let document = web_sys::window()
             .unwrap()
             .document()
@@ -146,43 +690,94 @@ impl Game {
 
         game.set_hidden(true);
 
+        let pass_button = document.get_element_by_id("playPass")
+            .unwrap()
+            .dyn_into::<HtmlElement>()?;
+
+        pass_button.set_hidden(false);
+
+        let confirm_button = document.get_element_by_id("playConfirm")
+            .unwrap()
+            .dyn_into::<HtmlElement>()?;
+
+        confirm_button.set_hidden(true);
+
+        self.staged = None;
+        self.touching = false;
+        self.touches.clear();
+        self.pinching = false;
+        self.drag = None;
+        self.view = View::default();
+        self.cursor = None;
+        self.typed.clear();
+        self.pending_move = None;
+
         let login = document.get_element_by_id("login")
             .unwrap()
             .dyn_into::<HtmlElement>()?;
 
         login.set_hidden(false);
 
-        STATE.lock()
+        // The lobby's rooms are replaced by the first list the next
+        // connection gets.
+        document.get_element_by_id("loginLobby")
             .unwrap()
-            .borrow_mut()
-            .ws = None;
+            .dyn_into::<HtmlElement>()?
+            .set_hidden(true);
+
+        self.ws = None;
+        self.turn_alert = None;
+
+        if let Some(render_frame) = self.render_frame.take() {
+            web_sys::window()
+                .unwrap()
+                .cancel_animation_frame(render_frame)?;
+        }
+
+        self.render_dirty = false;
 
-        Game::reset_game()?;
+        if self.release_pending {
+            self.release_pending = false;
+            self.tab_channel_post("released")?;
+        }
+
+        self.state.reset();
+
+        self.reset_game()?;
 
         Ok(())
     }
 
-    fn reset_game() -> JsError {
+    fn reset_game(&mut self) -> JsError {
         let document = web_sys::window()
             .unwrap()
             .document()
             .unwrap();
 
-        let game_status_room_players = document.get_element_by_id("gameStatusRoomPlayers")
-            .unwrap()
-            .dyn_into::<HtmlElement>()?;
-
         let game_status_chat_messages = document.get_element_by_id("gameStatusChatMessages")
             .unwrap()
             .dyn_into::<HtmlElement>()?;
 
-        game_status_room_players.set_inner_html("");
+        self.players.clear();
+        self.spectators.clear();
         game_status_chat_messages.set_inner_html("");
 
+        if let Some(spectator_list) = document.get_element_by_id("gameStatusRoomSpectators") {
+            spectator_list.remove();
+        }
+
+        if let Some(board_table) = document.get_element_by_id("gameBoardTable") {
+            board_table.remove();
+        }
+
+        if let Some(announcer) = document.get_element_by_id("gameAnnouncer") {
+            announcer.set_inner_html("");
+        }
+
         Ok(())
     }
 
-    fn on_button_login_submit() -> JsError {
+    fn on_button_login_submit(dispatcher: &Dispatcher) -> JsError {
         let document = web_sys::window()
             .unwrap()
             .document()
@@ -198,115 +793,119 @@ impl Game {
             .unwrap()
             .dyn_into::<HtmlElement>()?;
 
-        let on_button_submit = Closure::wrap(Box::new(move || {
-            let mut ws_exists = false;
+        let on_button_submit = dispatcher.callback(Event::LoginSubmit);
+
+        button_submit.set_onclick(Some(on_button_submit.as_ref().unchecked_ref()));
+
+        on_button_submit.forget();
+
+        Ok(())
+    }
+
+    fn connect(&mut self) -> JsError {
+        let document = web_sys::window()
+            .unwrap()
+            .document()
+            .unwrap();
+
+        if ! self.state.spectating {
+            App::request_notification_permission()?;
+        }
+
+        let mut ws_exists = false;
+
+        if self.ws.is_some() {
+            ws_exists = true;
+        }
 
-            if let Some(_) = STATE.lock()
+        if ! ws_exists {
+            let login_server = document.get_element_by_id("loginServer")
                 .unwrap()
-                .borrow()
-                .ws
-            {
-                ws_exists = true;
-            }
+                .dyn_into::<HtmlInputElement>()?;
 
-            if ! ws_exists {
-                let login_server = document.get_element_by_id("loginServer")
-                    .unwrap()
-                    .dyn_into::<HtmlInputElement>()?;
+            let ws_res = WebSocket::new(&login_server.value());
 
-                let ws_res = WebSocket::new(&login_server.value());
+            if let Ok(ws) = ws_res {
+                self.ws = Some(ws);
 
-                if let Ok(ws) = ws_res {
-                    STATE.lock()
+                let dispatcher = self.dispatcher();
+
+                let ws_onerror = dispatcher.callback(Event::WsError);
+                let ws_onopen = dispatcher.callback(Event::WsOpen);
+                let ws_onclose = dispatcher.callback(Event::WsClose);
+
+                let ws_ondecode = Closure::wrap(Box::new(move |e: ProgressEvent| {
+                    let reader: FileReader = e.target()
                         .unwrap()
-                        .borrow_mut()
-                        .ws = Some(ws);
-
-                    let ws_onerror = Closure::wrap(Box::new(|| {
-                        Game::on_ws_error()?;
-                        Ok::<(), JsValue>(())
-                    }) as Box<dyn FnMut() -> JsError>);
-
-                    let ws_onopen = Closure::wrap(Box::new(|| {
-                        Game::on_ws_open()?;
-                        Ok::<(), JsValue>(())
-                    }) as Box<dyn FnMut() -> JsError>);
-
-                    let ws_ondecode = Closure::wrap(Box::new(|e: ProgressEvent| {
-                        let reader: FileReader = e.target()
-                            .unwrap()
-                            .dyn_into()?;
-                        let result = reader.result()?;
-                        let buf = js_sys::Uint8Array::new(&result);
-                        let mut data = vec![0; buf.length() as usize];
-                        buf.copy_to(&mut data);
-                        Game::on_ws_message(&data)?;
-                        Ok::<(), JsValue>(())
-                    }) as Box<dyn FnMut(ProgressEvent) -> JsError>);
-                    
-                    let ws_onmessage = Closure::wrap(Box::new(move |e: MessageEvent| {
-                        let blob = e.data().dyn_into::<Blob>()?;
-                        let fr = FileReader::new()?;
-                        fr.add_event_listener_with_callback("load", &ws_ondecode.as_ref().unchecked_ref())?;
-                        fr.read_as_array_buffer(&blob)?;
-                        Ok::<(), JsValue>(())
-                    }) as Box<dyn FnMut(MessageEvent) -> JsError>);
-
-                    let ws_onclose = Closure::wrap(Box::new(|| {
-                        Game::on_ws_close()?;
-                        Ok::<(), JsValue>(())
-                    }) as Box<dyn FnMut() -> JsError>);
-
-                    Game::ws_add_event_listener_none("error", &ws_onerror)?;
-                    Game::ws_add_event_listener_none("open", &ws_onopen)?;
-                    Game::ws_add_event_listener("message", &ws_onmessage)?;
-                    Game::ws_add_event_listener_none("close", &ws_onclose)?;
-
-                    ws_onerror.forget();
-                    ws_onopen.forget();
-                    ws_onmessage.forget();
-                    ws_onclose.forget();
-                } else {
-                    Game::on_ws_error()?;
-                }
+                        .dyn_into()?;
+                    let result = reader.result()?;
+                    let buf = js_sys::Uint8Array::new(&result);
+                    let mut data = vec![0; buf.length() as usize];
+                    buf.copy_to(&mut data);
+                    dispatcher.dispatch(Event::WsMessage(data));
+                    Ok::<(), JsValue>(())
+                }) as Box<dyn FnMut(ProgressEvent) -> JsError>);
+            
+                let ws_onmessage = Closure::wrap(Box::new(move |e: MessageEvent| {
+                    let blob = e.data().dyn_into::<Blob>()?;
+                    let fr = FileReader::new()?;
+                    fr.add_event_listener_with_callback("load", ws_ondecode.as_ref().unchecked_ref())?;
+                    fr.read_as_array_buffer(&blob)?;
+                    Ok::<(), JsValue>(())
+                }) as Box<dyn FnMut(MessageEvent) -> JsError>);
+
+                self.ws_add_event_listener_none("error", &ws_onerror)?;
+                self.ws_add_event_listener_none("open", &ws_onopen)?;
+                self.ws_add_event_listener("message", &ws_onmessage)?;
+                self.ws_add_event_listener_none("close", &ws_onclose)?;
+
+                ws_onerror.forget();
+                ws_onopen.forget();
+                ws_onmessage.forget();
+                ws_onclose.forget();
             } else {
-                Game::on_ws_open()?;
+                self.on_ws_error()?;
             }
-
-            Ok::<(), JsValue>(())
-        }) as Box<dyn FnMut() -> JsError>);
-
-        button_submit.set_onclick(Some(on_button_submit.as_ref().unchecked_ref()));
-
-        on_button_submit.forget();
+        } else {
+            self.on_ws_open()?;
+        }
 
         Ok(())
     }
 
-    fn set_mouse_move() -> JsError {
+    fn set_spectate_button(dispatcher: &Dispatcher, locale: Locale) -> JsError {
         let document = web_sys::window()
             .unwrap()
             .document()
             .unwrap();
 
-        let canvas = document.get_element_by_id("gameBoard")
-            .unwrap()
+        let button_submit = document.get_element_by_id("loginSubmit")
+            .unwrap();
+
+        let button_spectate = document.create_element("button")?
             .dyn_into::<HtmlElement>()?;
+        button_spectate.set_id("loginSpectate");
+        button_spectate.set_class_name("btn btn-secondary");
+        button_spectate.set_attribute("type", "button")?;
+        button_spectate.set_inner_text(&locale.text(Message::Spectate));
 
-        let mouse_move_handler = Closure::wrap(Box::new(|e: MouseEvent| {
-            Game::on_mouse_move(e.offset_x(), e.offset_y())?;
-            Game::render()?;
-            Ok::<(), JsValue>(())
-        }) as Box<dyn FnMut(MouseEvent) -> JsError>);
+        let on_button_spectate = dispatcher.callback(Event::SpectateSubmit);
+
+        button_spectate.set_onclick(Some(on_button_spectate.as_ref().unchecked_ref()));
 
-        canvas.set_onmousemove(Some(mouse_move_handler.as_ref().unchecked_ref()));
+        on_button_spectate.forget();
 
-        mouse_move_handler.forget();
+        button_submit.insert_adjacent_element("afterend", &button_spectate)?;
 
         Ok(())
     }
 
-    fn set_mouse_click() -> JsError {
+    /// Routes pointer events on the board. Mice hover and place on release,
+    /// or stage first in confirm mode, and pan when dragged; touch and pen
+    /// drag a ghost stone around and place it through the confirm button, so
+    /// a stray tap never commits a move. Two fingers pinch to zoom, as does
+    /// the mouse wheel.
+    fn set_pointer_input(dispatcher: &Dispatcher) -> JsError {
         let document = web_sys::window()
             .unwrap()
             .document()
@@ -316,20 +915,114 @@ impl Game {
             .unwrap()
             .dyn_into::<HtmlElement>()?;
 
-        let mouse_click_handler = Closure::wrap(Box::new(|e: MouseEvent| {
-            Game::on_mouse_click(e.offset_x(), e.offset_y())?;
-            Game::render()?;
+        // Only the board swallows touch gestures; the rest of the page still
+        // scrolls and zooms as usual.
+        canvas.style()
+            .set_property("touch-action", "none")?;
+
+        let down_dispatcher = dispatcher.clone();
+        let pointer_down_handler = Closure::wrap(Box::new(move |e: PointerEvent| {
+            if e.pointer_type() == "mouse" && e.button() != 0 {
+                return Ok(());
+            }
+
+            e.target()
+                .unwrap()
+                .dyn_into::<HtmlElement>()?
+                .set_pointer_capture(e.pointer_id())?;
+
+            if e.pointer_type() == "mouse" {
+                down_dispatcher.dispatch(Event::MouseDown(e.offset_x(), e.offset_y()));
+            } else {
+                down_dispatcher.dispatch(Event::TouchDown(e.pointer_id(), e.offset_x(), e.offset_y()));
+            }
+
+            Ok::<(), JsValue>(())
+        }) as Box<dyn FnMut(PointerEvent) -> JsError>);
+
+        let move_dispatcher = dispatcher.clone();
+        let pointer_move_handler = Closure::wrap(Box::new(move |e: PointerEvent| {
+            if e.pointer_type() == "mouse" {
+                move_dispatcher.dispatch(Event::MouseMove(e.offset_x(), e.offset_y()));
+            } else {
+                move_dispatcher.dispatch(Event::TouchMove(e.pointer_id(), e.offset_x(), e.offset_y()));
+            }
+
+            Ok::<(), JsValue>(())
+        }) as Box<dyn FnMut(PointerEvent) -> JsError>);
+
+        let up_dispatcher = dispatcher.clone();
+        let pointer_up_handler = Closure::wrap(Box::new(move |e: PointerEvent| {
+            if e.pointer_type() == "mouse" {
+                if e.button() == 0 {
+                    up_dispatcher.dispatch(Event::MouseClick(e.offset_x(), e.offset_y()));
+                }
+            } else {
+                up_dispatcher.dispatch(Event::TouchUp(e.pointer_id(), e.offset_x(), e.offset_y()));
+            }
+
             Ok::<(), JsValue>(())
-        }) as Box<dyn FnMut(MouseEvent) -> JsError>);
+        }) as Box<dyn FnMut(PointerEvent) -> JsError>);
 
-        canvas.set_onmouseup(Some(mouse_click_handler.as_ref().unchecked_ref()));
+        let leave_dispatcher = dispatcher.clone();
+        let pointer_leave_handler = Closure::wrap(Box::new(move |e: PointerEvent| {
+            if e.pointer_type() == "mouse" {
+                leave_dispatcher.dispatch(Event::MouseOut);
+            }
+
+            Ok::<(), JsValue>(())
+        }) as Box<dyn FnMut(PointerEvent) -> JsError>);
+
+        let cancel_dispatcher = dispatcher.clone();
+        let pointer_cancel_handler = Closure::wrap(Box::new(move |e: PointerEvent| {
+            if e.pointer_type() == "mouse" {
+                cancel_dispatcher.dispatch(Event::MouseOut);
+            } else {
+                cancel_dispatcher.dispatch(Event::TouchCancel(e.pointer_id()));
+            }
+
+            Ok::<(), JsValue>(())
+        }) as Box<dyn FnMut(PointerEvent) -> JsError>);
 
-        mouse_click_handler.forget();
+        let wheel_dispatcher = dispatcher.clone();
+        let wheel_handler = Closure::wrap(Box::new(move |e: WheelEvent| {
+            e.prevent_default();
+
+            // Line and page deltas come from notched wheels; scale them to
+            // roughly what a touchpad reports in pixels.
+            let delta = match e.delta_mode() {
+                WheelEvent::DOM_DELTA_LINE => e.delta_y() * 16_f64,
+                WheelEvent::DOM_DELTA_PAGE => e.delta_y() * 400_f64,
+                _ => e.delta_y(),
+            };
+
+            wheel_dispatcher.dispatch(Event::Zoom((-delta * 0.002_f64).exp(), e.offset_x(), e.offset_y()));
+
+            Ok::<(), JsValue>(())
+        }) as Box<dyn FnMut(WheelEvent) -> JsError>);
+
+        canvas.set_onpointerdown(Some(pointer_down_handler.as_ref().unchecked_ref()));
+        canvas.set_onpointermove(Some(pointer_move_handler.as_ref().unchecked_ref()));
+        canvas.set_onpointerup(Some(pointer_up_handler.as_ref().unchecked_ref()));
+        canvas.set_onpointerleave(Some(pointer_leave_handler.as_ref().unchecked_ref()));
+        canvas.set_onpointercancel(Some(pointer_cancel_handler.as_ref().unchecked_ref()));
+        canvas.set_onwheel(Some(wheel_handler.as_ref().unchecked_ref()));
+
+        pointer_down_handler.forget();
+        pointer_move_handler.forget();
+        pointer_up_handler.forget();
+        pointer_leave_handler.forget();
+        pointer_cancel_handler.forget();
+        wheel_handler.forget();
 
         Ok(())
     }
 
-    fn set_mouse_out() -> JsError {
+    /// Lets the focused board be played from the keyboard: arrows move a
+    /// cursor, Enter or Space place at it, and typing a coordinate such as
+    /// "D4" jumps there. Shift+Enter passes; letters are left to
+    /// coordinates, as any of them can be a column on the larger boards.
+    fn set_keyboard_input(dispatcher: &Dispatcher, locale: Locale) -> JsError {
         let document = web_sys::window()
             .unwrap()
             .document()
@@ -339,1107 +1032,2370 @@ impl Game {
             .unwrap()
             .dyn_into::<HtmlElement>()?;
 
-        let mouse_out_handler = Closure::wrap(Box::new(|| {
-            Game::on_mouse_out()?;
-            Game::render()?;
-            Ok::<(), JsValue>(())
-        }) as Box<dyn FnMut() -> JsError>);
+        canvas.set_tab_index(0);
+        canvas.set_attribute("role", "application")?;
+        canvas.set_attribute("aria-roledescription", "board")?;
+        canvas.set_attribute("aria-label", &locale.text(Message::BoardDescription))?;
 
-        canvas.set_onmouseout(Some(mouse_out_handler.as_ref().unchecked_ref()));
+        let key_dispatcher = dispatcher.clone();
+        let key_handler = Closure::wrap(Box::new(move |e: KeyboardEvent| {
+            if e.ctrl_key() || e.alt_key() || e.meta_key() {
+                return Ok(());
+            }
 
-        mouse_out_handler.forget();
+            let key = e.key();
+
+            let event = match key.as_str() {
+                "ArrowLeft" => Event::CursorMove(-1, 0),
+                "ArrowRight" => Event::CursorMove(1, 0),
+                "ArrowUp" => Event::CursorMove(0, -1),
+                "ArrowDown" => Event::CursorMove(0, 1),
+                "Enter" if e.shift_key() => Event::Pass,
+                "Enter" | " " => Event::CursorPlace,
+                _ => {
+                    let mut chars = key.chars();
+
+                    match (chars.next(), chars.next()) {
+                        (Some(c), None) if c.is_ascii_alphanumeric() =>
+                            Event::CursorType(c),
+                        _ => return Ok(()),
+                    }
+                }
+            };
 
-        Ok(())
-    }
+            // Keeps arrows and Space from scrolling the page underneath.
+            e.prevent_default();
 
-    fn get_window_size() -> u32 {
-        let window = web_sys::window()
-            .unwrap();
+            key_dispatcher.dispatch(event);
 
-        let mut inner_width = window.inner_width()
-            .unwrap()
-            .as_f64()
-            .unwrap() * (2_f64 / 3_f64);
+            Ok::<(), JsValue>(())
+        }) as Box<dyn FnMut(KeyboardEvent) -> JsError>);
 
-        let inner_height = window.inner_height()
-            .unwrap()
-            .as_f64()
-            .unwrap() * (9_f64 / 10_f64);
+        canvas.set_onkeydown(Some(key_handler.as_ref().unchecked_ref()));
 
-        if inner_width > inner_height {
-            inner_width = inner_height;
-        }
+        key_handler.forget();
 
-        inner_width as u32
+        Ok(())
     }
 
-    fn on_window_resize() -> JsError {
-        let window = web_sys::window()
+    fn set_confirm_button(dispatcher: &Dispatcher, locale: Locale) -> JsError {
+        let document = web_sys::window()
+            .unwrap()
+            .document()
             .unwrap();
 
-        let on_resize = Closure::wrap(Box::new(|| {
-            BOARD_SIZE.lock()
-                .unwrap()
-                .replace(Game::get_window_size());
+        let pass_button = document.get_element_by_id("playPass")
+            .unwrap();
+
+        let confirm_button = document.create_element("button")?
+            .dyn_into::<HtmlElement>()?;
+        confirm_button.set_id("playConfirm");
+        confirm_button.set_class_name("btn btn-success");
+        confirm_button.set_attribute("type", "button")?;
+        confirm_button.set_inner_text(&locale.text(Message::Confirm));
+        confirm_button.set_hidden(true);
 
-            Game::status_bar_size()?;
-            Game::render()?;
+        let on_confirm = dispatcher.callback(Event::ConfirmPlace);
 
-            Ok::<(), JsValue>(())
-        }) as Box<dyn FnMut() -> JsError>);
+        confirm_button.set_onclick(Some(on_confirm.as_ref().unchecked_ref()));
 
-        window.set_onresize(Some(on_resize.as_ref().unchecked_ref()));
+        on_confirm.forget();
 
-        on_resize.forget();
+        pass_button.insert_adjacent_element("beforebegin", &confirm_button)?;
 
         Ok(())
     }
 
-    fn ws_add_event_listener<T>(event: &str, handler: &JsClosure<T>) -> JsError {
-        STATE.lock()
-            .unwrap()
-            .borrow()
-            .ws
-            .as_deref()
-            .unwrap()
-            .add_event_listener_with_callback(event, handler.as_ref().unchecked_ref())?;
-
-        Ok(())
-    }
-
-    fn ws_add_event_listener_none(event: &str, handler: &JsClosureNone) -> JsError {
-        STATE.lock()
-            .unwrap()
-            .borrow()
-            .ws
-            .as_deref()
-            .unwrap()
-            .add_event_listener_with_callback(event, handler.as_ref().unchecked_ref())?;
-
-        Ok(())
-    }
-
-    fn ws_send_message(message: &ClientMessage) -> JsError {
-        let message_encoded = bincode::serialize(message)
-            .map_err(|e| JsValue::from_str(
-                    &format!("Could not serialize ClientMessage: {}", e)))?;
-        STATE.lock()
-            .unwrap()
-            .borrow()
-            .ws
-            .as_ref()
+    fn set_reset_view_button(dispatcher: &Dispatcher, locale: Locale) -> JsError {
+        let document = web_sys::window()
             .unwrap()
-            .send_with_u8_array(&message_encoded)?;
+            .document()
+            .unwrap();
 
-        Ok(())
-    }
+        let pass_button = document.get_element_by_id("playPass")
+            .unwrap();
 
-    fn on_ws_message(message: &[u8]) -> JsError {
-        let server_message = bincode::deserialize::<ServerMessage>(message)
-            .map_err(|e| JsValue::from_str(
-                    &format!("Could not deserialize ServerMessage: {}", e)))?;
+        let reset_view_button = document.create_element("button")?
+            .dyn_into::<HtmlElement>()?;
+        reset_view_button.set_id("playResetView");
+        reset_view_button.set_class_name("btn btn-secondary");
+        reset_view_button.set_attribute("type", "button")?;
+        reset_view_button.set_inner_text(&locale.text(Message::ResetView));
+        reset_view_button.set_hidden(true);
 
-        match server_message {
-            ServerMessage::LoginResponse(result) =>
-                Game::on_login_response(result)?,
-            ServerMessage::RoomCreateResponse(result) =>
-                Game::on_room_create_response(result)?,
-            ServerMessage::PlaceResponse(result) =>
-                Game::on_place_response(result)?,
-            ServerMessage::PlayerAdd(player) =>
-                Game::on_player_add(player)?,
-            ServerMessage::PlayerRemove(username) =>
-                Game::on_player_remove(username)?,
-            ServerMessage::NextTurn(username) =>
-                Game::on_next_turn(username)?,
-            ServerMessage::Chat(message) =>
-                Game::on_chat_message_received(message)?,
-            ServerMessage::AlreadyLoggedIn =>
-                Game::on_player_already_logged_in()?
-        }
+        let on_reset_view = dispatcher.callback(Event::ResetView);
 
-        Ok(())
-    }
+        reset_view_button.set_onclick(Some(on_reset_view.as_ref().unchecked_ref()));
 
-    fn on_login_response(result: Result<Room, LoginError>) -> JsError {
-        Game::login_form_reset()?;
+        on_reset_view.forget();
 
-        match result {
-            Ok(room) => Game::on_login_response_success(room)?,
-
-            Err(login_error) => match login_error {
-                LoginError::RoomFull =>
-                    Game::on_login_response_room_full()?,
-                LoginError::UsernameTaken =>
-                    Game::on_login_response_username_taken()?,
-                LoginError::RoomNameTooLong =>
-                    Game::on_login_response_room_name_too_long()?,
-                LoginError::UsernameTooLong =>
-                    Game::on_login_response_username_too_long()?,
-                LoginError::RoomDoesNotExist(room_name) =>
-                    Game::on_login_response_room_does_not_exist(room_name)?,
-            }
-        }
+        pass_button.insert_adjacent_element("beforebegin", &reset_view_button)?;
 
         Ok(())
     }
 
-    fn on_login_response_success(room: Room) -> JsError {
-        let current_player = room.current_player.clone();
-
-        STATE.lock()
-            .unwrap()
-            .borrow_mut()
-            .room = Some(room);
-
+    /// Buttons turning the board for whoever is looking at it, e.g. to see
+    /// it from White's side.
+    fn set_orientation_buttons(dispatcher: &Dispatcher, locale: Locale) -> JsError {
         let document = web_sys::window()
             .unwrap()
             .document()
             .unwrap();
 
-        let login_form = document.get_element_by_id("login")
-            .unwrap()
-            .dyn_into::<HtmlElement>()?;
+        let pass_button = document.get_element_by_id("playPass")
+            .unwrap();
 
-        login_form.set_hidden(true);
+        let orientation_group = document.create_element("div")?;
+        orientation_group.set_id("playOrientation");
+        orientation_group.set_class_name("btn-group");
+        orientation_group.set_attribute("role", "group")?;
+        orientation_group.set_attribute("aria-label", &locale.text(Message::BoardOrientation))?;
 
-        let room_el = document.get_element_by_id("loginRoom")
-            .unwrap()
-            .dyn_into::<HtmlInputElement>()?;
+        let buttons = [
+            (Message::Rotate, Message::RotateDescription, Event::RotateView),
+            (Message::FlipHorizontal, Message::FlipHorizontalDescription, Event::FlipHorizontal),
+            (Message::FlipVertical, Message::FlipVerticalDescription, Event::FlipVertical),
+        ];
 
+        for (text, label, event) in buttons.iter() {
+            let button = document.create_element("button")?
+                .dyn_into::<HtmlElement>()?;
+            button.set_class_name("btn btn-secondary");
+            button.set_attribute("type", "button")?;
+            button.set_attribute("aria-label", &locale.text(*label))?;
+            button.set_inner_text(&locale.text(*text));
 
-        Game::status_bar_size()?;
-        Game::status_bar_header(room_el.value())?;
+            let on_click = dispatcher.callback(event.clone());
 
-        let current_player_el = document.get_element_by_id(&format!("player-{}", current_player))
-            .unwrap()
-            .dyn_into::<HtmlElement>()?;
+            button.set_onclick(Some(on_click.as_ref().unchecked_ref()));
 
-        current_player_el.style()
-            .set_property("background-color", "grey")?;
-        
-        Game::render()?;
+            on_click.forget();
+
+            orientation_group.append_child(&button)?;
+        }
+
+        pass_button.insert_adjacent_element("beforebegin", &orientation_group)?;
 
         Ok(())
     }
 
-    fn on_login_response_room_full() -> JsError {
+    fn set_confirm_moves_toggle(dispatcher: &Dispatcher, locale: Locale) -> JsError {
         let document = web_sys::window()
             .unwrap()
             .document()
             .unwrap();
 
-        let login_room = document.get_element_by_id("loginRoom")
+        let pass_button = document.get_element_by_id("playPass")
             .unwrap();
 
-        login_room.set_class_name("form-control is-invalid");
+        let toggle = document.create_element("div")?;
+        toggle.set_class_name("custom-control custom-switch");
 
-        let login_room_error = document
-            .get_element_by_id("loginRoomError");
+        let toggle_input = document.create_element("input")?
+            .dyn_into::<HtmlInputElement>()?;
+        toggle_input.set_id("playConfirmMoves");
+        toggle_input.set_class_name("custom-control-input");
+        toggle_input.set_type("checkbox");
+        toggle_input.set_checked(App::preference_load(CONFIRM_MOVES_KEY)
+                                     .is_some_and(|value| value == "true"));
+        toggle.append_child(&toggle_input)?;
+
+        let toggle_label = document.create_element("label")?
+            .dyn_into::<HtmlElement>()?;
+        toggle_label.set_class_name("custom-control-label");
+        toggle_label.set_attribute("for", "playConfirmMoves")?;
+        toggle_label.set_inner_text(&locale.text(Message::ConfirmMoves));
+        toggle.append_child(&toggle_label)?;
 
-        if let None = login_room_error {
-            let login_room_error = document.create_element("div")?
-                .dyn_into::<HtmlElement>()?;
-            login_room_error.set_id("loginRoomError");
-            login_room_error.set_class_name("invalid-feedback");
-            login_room_error.set_inner_text("Could not join room: Room is full");
+        let on_toggle = dispatcher.callback(Event::ConfirmMovesToggle);
 
-            let login_room_form = document.get_element_by_id("loginRoomForm")
-                .unwrap();
+        toggle_input.set_onchange(Some(on_toggle.as_ref().unchecked_ref()));
 
-            login_room_form.append_child(&login_room_error)?;
-        } else {
-            login_room_error
-                .unwrap()
-                .dyn_into::<HtmlElement>()?
-                .set_inner_text("Could not join room: Room is full");
-        }
+        on_toggle.forget();
+
+        pass_button.insert_adjacent_element("beforebegin", &toggle)?;
 
         Ok(())
     }
 
-    fn on_login_response_username_taken() -> JsError {
+    /// Language picker on the login form. Picking another language reloads
+    /// the page, as everything already shown is in the old one.
+    fn set_locale_select(dispatcher: &Dispatcher, locale: Locale) -> JsError {
         let document = web_sys::window()
             .unwrap()
             .document()
             .unwrap();
 
-        let login_username = document.get_element_by_id("loginUsername")
-            .unwrap();
-
-        login_username.set_class_name("form-control is-invalid");
-
-        let login_username_error = document
-            .get_element_by_id("loginUsernameError");
+        let mut select = dom::element("select")
+            .id("loginLocale")
+            .class("custom-select");
 
-        if let None = login_username_error {
-            let login_username_error = document.create_element("div")?
-                .dyn_into::<HtmlElement>()?;
-            login_username_error.set_id("loginUsernameError");
-            login_username_error.set_class_name("invalid-feedback");
-            login_username_error.set_inner_text("Invalid username: Username is taken");
+        for option_locale in Locale::ALL.iter() {
+            let mut option = dom::element("option")
+                .attribute("value", option_locale.tag())
+                .attribute("lang", option_locale.tag());
 
-            let login_username_form = document.get_element_by_id("loginUsernameForm")
-                .unwrap();
+            if *option_locale == locale {
+                option = option.attribute("selected", "");
+            }
 
-            login_username_form.append_child(&login_username_error)?;
-        } else {
-            login_username_error
-                .unwrap()
-                .dyn_into::<HtmlElement>()?
-                .set_inner_text("Invalid username: Username is taken");
+            select = select.child(option.text(option_locale.name()));
         }
 
-        Ok(())
-    }
+        let locale_form = dom::element("div")
+            .id("loginLocaleForm")
+            .class("form-group")
+            .child(dom::element("label")
+                .attribute("for", "loginLocale")
+                .text(locale.text(Message::Language)))
+            .child(select)
+            .build()?;
 
-    fn on_login_response_room_name_too_long() -> JsError {
-        let document = web_sys::window()
+        let select = locale_form.last_element_child()
             .unwrap()
-            .document()
-            .unwrap();
+            .dyn_into::<HtmlElement>()?;
 
-        let login_room = document.get_element_by_id("loginRoom")
-            .unwrap();
+        let on_change = dispatcher.callback(Event::LocaleChange);
 
-        login_room.set_class_name("form-control is-invalid");
+        select.set_onchange(Some(on_change.as_ref().unchecked_ref()));
 
-        let login_room_error = document
-            .get_element_by_id("loginRoomError");
+        on_change.forget();
 
-        if let None = login_room_error {
-            let login_room_error = document.create_element("div")?
-                .dyn_into::<HtmlElement>()?;
-            login_room_error.set_id("loginRoomError");
-            login_room_error.set_class_name("invalid-feedback");
-            login_room_error.set_inner_text("Room name too long: Max room name is 16 characters");
+        document.get_element_by_id("login")
+            .unwrap()
+            .append_child(&locale_form)?;
 
-            let login_room_form = document.get_element_by_id("loginRoomForm")
-                .unwrap();
+        Ok(())
+    }
 
-            login_room_form.append_child(&login_room_error)?;
-        } else {
-            login_room_error
-                .unwrap()
-                .dyn_into::<HtmlElement>()?
-                .set_inner_text("Room name too long: Max room name is 16 characters");
-        }
+    fn on_locale_change() -> JsError {
+        let window = web_sys::window()
+            .unwrap();
+
+        let select = window.document()
+            .unwrap()
+            .get_element_by_id("loginLocale")
+            .unwrap()
+            .dyn_into::<HtmlSelectElement>()?;
+
+        App::preference_store(LOCALE_KEY, &select.value())?;
+
+        window.location()
+            .reload()?;
 
         Ok(())
     }
 
-    fn on_login_response_username_too_long() -> JsError {
+    fn on_confirm_moves_toggle(&mut self) -> JsError {
         let document = web_sys::window()
             .unwrap()
             .document()
             .unwrap();
 
-        let login_username = document.get_element_by_id("loginUsername")
-            .unwrap();
-
-        login_username.set_class_name("form-control is-invalid");
-
-        let login_username_error = document
-            .get_element_by_id("loginUsernameError");
+        let toggle_input = document.get_element_by_id("playConfirmMoves")
+            .unwrap()
+            .dyn_into::<HtmlInputElement>()?;
 
-        if let None = login_username_error {
-            let login_username_error = document.create_element("div")?
-                .dyn_into::<HtmlElement>()?;
-            login_username_error.set_id("loginUsernameError");
-            login_username_error.set_class_name("invalid-feedback");
-            login_username_error.set_inner_text("Username too long: Max length is 16 characters");
+        self.confirm_moves = toggle_input.checked();
 
-            let login_username_form = document.get_element_by_id("loginUsernameForm")
-                .unwrap();
+        App::preference_store(CONFIRM_MOVES_KEY, if self.confirm_moves { "true" } else { "false" })?;
 
-            login_username_form.append_child(&login_username_error)?;
-        } else {
-            login_username_error
-                .unwrap()
-                .dyn_into::<HtmlElement>()?
-                .set_inner_text("Username too long: Max length is 16 characters");
-        }
         Ok(())
     }
 
-    fn on_login_response_room_does_not_exist(room_name: String) -> JsError {
+    fn set_sound_controls(dispatcher: &Dispatcher, locale: Locale) -> JsError {
         let document = web_sys::window()
             .unwrap()
             .document()
             .unwrap();
 
-        let header_message = document.create_element("h1")?
-            .dyn_into::<HtmlElement>()?;
-        header_message.set_inner_text("Create The Room");
-
-        let status_header = document.get_element_by_id("statusHeader")
+        let pass_button = document.get_element_by_id("playPass")
             .unwrap();
-        status_header.append_child(&header_message)?;
 
-        let status_paragraph = document.create_element("p")?
-            .dyn_into::<HtmlElement>()?;
-        status_paragraph.set_inner_text(&format!(r#"The room "{}" does not exist yet. Please create it below."#, room_name));
-
-        let status_form = document.create_element("form")?;
-        status_form.set_inner_html(r#"
-            <br>
-            <div class="form-group">
-                <div class="form-row">
-                    <div class="col-4">
-                        <label>Board Length</label>
-                    </div>
-                    <div class="col-8">
-                        <input type="text" class="form-control" id="boardLength" placeholder="Board Length">
-                    </div>
-                </div>
-                <div class="invalid-feedback" id="invalidDimensions"></div>
-            </div>
-            <br>
-            <div class="form-group">
-                <div class="form-row">
-                    <div class="col-4">
-                        <label for="roomCapacity">Room Capacity</label>
-                    </div>
-                    <div class="col-8">
-                        <input type="text" class="form-control" id="roomCapacity" placeholder="Room Capacity">
-                    </div>
-                </div>
-                <div class="invalid-feedback" id="invalidCapacity"></div>
-            </div>"#);
-
-        let status_body = document.get_element_by_id("statusBody")
-            .unwrap();
-        status_body.append_child(&status_paragraph)?;
-        status_body.append_child(&status_form)?;
-
-
-        let status_footer = document.get_element_by_id("statusFooter")
-            .unwrap();
-
-        let quit_button = document.create_element("button")?
-            .dyn_into::<HtmlElement>()?;
-        quit_button.set_class_name("btn btn-secondary");
-        quit_button.set_attribute("type", "button")?;
-        quit_button.set_attribute("data-dismiss", "modal")?;
-        quit_button.set_inner_text("Quit");
+        let sound = document.create_element("div")?;
+        sound.set_id("playSound");
+
+        let toggle = document.create_element("div")?;
+        toggle.set_class_name("custom-control custom-switch");
+
+        let toggle_input = document.create_element("input")?
+            .dyn_into::<HtmlInputElement>()?;
+        toggle_input.set_id("playSoundEnabled");
+        toggle_input.set_class_name("custom-control-input");
+        toggle_input.set_type("checkbox");
+        toggle_input.set_checked(true);
+        toggle.append_child(&toggle_input)?;
 
-        let create_button = document.create_element("button")?
+        let toggle_label = document.create_element("label")?
             .dyn_into::<HtmlElement>()?;
-        create_button.set_class_name("btn btn-primary");
-        create_button.set_attribute("type", "button")?;
-        create_button.set_inner_text("Create");
+        toggle_label.set_class_name("custom-control-label");
+        toggle_label.set_attribute("for", "playSoundEnabled")?;
+        toggle_label.set_inner_text(&locale.text(Message::Sound));
+        toggle.append_child(&toggle_label)?;
 
-        let create_button_handle = Closure::wrap(Box::new(move || {
-            Game::on_room_create_button_submit(room_name.as_str())?;
-            Ok::<(), JsValue>(())
-        }) as Box<dyn FnMut() -> JsError>);
+        sound.append_child(&toggle)?;
 
-        let quit_button_handle = Closure::wrap(Box::new(move || {
-            Game::status_modal_reset()?;
+        let volume = document.create_element("input")?
+            .dyn_into::<HtmlInputElement>()?;
+        volume.set_id("playVolume");
+        volume.set_class_name("custom-range");
+        volume.set_type("range");
+        volume.set_min("0");
+        volume.set_max("100");
+        volume.set_value("50");
+        volume.set_attribute("aria-label", &locale.text(Message::Volume))?;
+        sound.append_child(&volume)?;
 
-            STATE.lock()
-                .unwrap()
-                .borrow()
-                .ws
-                .as_ref()
-                .unwrap()
-                .close()?;
+        let on_toggle = dispatcher.callback(Event::SoundToggle);
+        let on_volume = dispatcher.callback(Event::VolumeChange);
 
-            STATE.lock()
-                .unwrap()
-                .borrow_mut()
-                .ws = None;
+        toggle_input.set_onchange(Some(on_toggle.as_ref().unchecked_ref()));
+        volume.set_oninput(Some(on_volume.as_ref().unchecked_ref()));
 
-            Ok::<(), JsValue>(())
-        }) as Box<dyn FnMut() -> JsError>);
+        on_toggle.forget();
+        on_volume.forget();
 
-        create_button.set_onclick(Some(create_button_handle.as_ref().unchecked_ref()));
-        quit_button.set_onclick(Some(quit_button_handle.as_ref().unchecked_ref()));
+        pass_button.insert_adjacent_element("beforebegin", &sound)?;
 
-        create_button_handle.forget();
-        quit_button_handle.forget();
+        Ok(())
+    }
 
-        status_footer.append_child(&quit_button)?;
-        status_footer.append_child(&create_button)?;
+    /// Storage key for one of the sound settings of the logged in user.
+    fn sound_setting_key(&self, setting: &str) -> Option<String> {
+        self.state.room
+            .as_ref()
+            .map(|room| format!("rengo-{}:{}", setting, room.self_player))
+    }
 
-        let status = document.get_element_by_id("status")
+    /// Restores the sound settings saved for the user who just joined and
+    /// shows them in the controls.
+    fn load_sound_settings(&mut self) -> JsError {
+        let document = web_sys::window()
+            .unwrap()
+            .document()
             .unwrap();
 
-        status.set_attribute("data-backdrop", "static")?;
-        status.set_attribute("data-keyboard", "false")?;
+        let defaults = Audio::default();
+
+        self.audio.muted = self.sound_setting_key("muted")
+            .and_then(|key| App::preference_load(&key))
+            .map_or(defaults.muted, |value| value == "true");
 
-        let show_modal = js_sys::Function::new_with_args("name", "$(name).modal('show')");
-        show_modal.call1(&JsValue::null(), &JsValue::from_str("#status"))?;
+        self.audio.volume = self.sound_setting_key("volume")
+            .and_then(|key| App::preference_load(&key))
+            .and_then(|value| value.parse::<f64>().ok())
+            .map_or(defaults.volume, |volume| volume.clamp(0_f64, 1_f64));
 
-        Game::login_form_reset()?;
+        document.get_element_by_id("playSoundEnabled")
+            .unwrap()
+            .dyn_into::<HtmlInputElement>()?
+            .set_checked(! self.audio.muted);
+
+        document.get_element_by_id("playVolume")
+            .unwrap()
+            .dyn_into::<HtmlInputElement>()?
+            .set_value(&((self.audio.volume * 100_f64).round() as u32).to_string());
 
         Ok(())
     }
 
-    fn on_room_create_button_submit(room_name: &str) -> JsError {
+    fn on_sound_toggle(&mut self) -> JsError {
         let document = web_sys::window()
             .unwrap()
             .document()
             .unwrap();
 
-        let capacity_err = document.get_element_by_id("invalidCapacity")
+        let toggle_input = document.get_element_by_id("playSoundEnabled")
             .unwrap()
-            .dyn_into::<HtmlElement>()?;
-        capacity_err.set_inner_text("");
+            .dyn_into::<HtmlInputElement>()?;
 
-        let dimensions_err = document.get_element_by_id("invalidDimensions")
-            .unwrap()
-            .dyn_into::<HtmlElement>()?;
-        dimensions_err.set_inner_text("");
+        self.audio.muted = ! toggle_input.checked();
+
+        if let Some(key) = self.sound_setting_key("muted") {
+            App::preference_store(&key, if self.audio.muted { "true" } else { "false" })?;
+        }
+
+        Ok(())
+    }
 
-        let mut error = false;
+    fn on_volume_change(&mut self) -> JsError {
+        let document = web_sys::window()
+            .unwrap()
+            .document()
+            .unwrap();
 
-        let capacity = document.get_element_by_id("roomCapacity")
+        let volume = document.get_element_by_id("playVolume")
             .unwrap()
             .dyn_into::<HtmlInputElement>()?;
 
-        capacity.set_class_name("form-control");
+        self.audio.volume = volume.value_as_number() / 100_f64;
 
-        let capacity = capacity.value() 
-            .parse::<u8>()
-            .map_err(|e| JsValue::from_str(&format!("Unable to parse roomCapacity: {}", e)));
+        if let Some(key) = self.sound_setting_key("volume") {
+            App::preference_store(&key, &self.audio.volume.to_string())?;
+        }
 
-        if let Err(_) = capacity {
-            let capacity_el = document.get_element_by_id("roomCapacity")
-                .unwrap()
-                .dyn_into::<HtmlElement>()?;
-            capacity_el.set_class_name("form-control is-invalid");
+        Ok(())
+    }
 
-            let capacity_err = document.get_element_by_id("invalidCapacity")
-                .unwrap()
-                .dyn_into::<HtmlElement>()?;
-            capacity_err.set_inner_text(r#"Invalid Capacity. Enter a positive integer such as "4"."#);
-            error = true;
-        }
+    fn get_window_size() -> u32 {
+        let window = web_sys::window()
+            .unwrap();
 
-        let board_size = document.get_element_by_id("boardLength")
+        let mut inner_width = window.inner_width()
             .unwrap()
-            .dyn_into::<HtmlInputElement>()?;
-        board_size.set_class_name("form-control");
-
-        let board_size = board_size.value()
-            .parse::<u8>()
-            .map_err(|e| JsValue::from_str(&format!("Unable to parse boardLength: {}", e)));
+            .as_f64()
+            .unwrap() * (2_f64 / 3_f64);
 
-        if let Err(_) = board_size {
-            let dimensions_el = document.get_element_by_id("boardLength")
-                .unwrap()
-                .dyn_into::<HtmlElement>()?;
-            dimensions_el.set_class_name("form-control is-invalid");
+        let inner_height = window.inner_height()
+            .unwrap()
+            .as_f64()
+            .unwrap() * (9_f64 / 10_f64);
 
-            let dimensions_err = document.get_element_by_id("invalidDimensions")
-                .unwrap()
-                .dyn_into::<HtmlElement>()?;
-            dimensions_err.set_inner_text(r#"Invalid Dimensions. Enter a positive integer such as "9"."#);
-            error = true;
+        if inner_width > inner_height {
+            inner_width = inner_height;
         }
 
-        if error {
-            return Err(JsValue::from_str(&"Invalid input"));
-        }
+        inner_width as u32
+    }
+
+    fn set_window_resize(dispatcher: &Dispatcher) -> JsError {
+        let window = web_sys::window()
+            .unwrap();
 
-        let create_room = ClientMessage::RoomCreate(String::from(room_name), capacity?, board_size.clone()?, board_size?);
+        let on_resize = dispatcher.callback(Event::Resize);
+
+        window.set_onresize(Some(on_resize.as_ref().unchecked_ref()));
+
+        on_resize.forget();
 
-        Game::ws_send_message(&create_room)?;
         Ok(())
     }
 
-    fn status_modal_reset() -> JsError {
-        let document = web_sys::window()
+    /// Redraws the board once its wood has loaded, in case it was first
+    /// drawn without it.
+    fn set_board_image_load(dispatcher: &Dispatcher) -> JsError {
+        let image = web_sys::window()
             .unwrap()
             .document()
-            .unwrap();
+            .unwrap()
+            .get_element_by_id("backgroundWood")
+            .unwrap()
+            .dyn_into::<HtmlImageElement>()?;
 
-        let status_modal = document.get_element_by_id("status")
-            .unwrap();
+        let on_load = dispatcher.callback(Event::BoardImageLoad);
+
+        image.set_onload(Some(on_load.as_ref().unchecked_ref()));
+
+        on_load.forget();
 
-        status_modal.remove();
+        Ok(())
+    }
+
+    fn on_board_image_load(&mut self) -> JsError {
+        if self.layers.is_some() {
+            self.request_render()?;
+        }
 
-        let status_modal = document.create_element("div")?;
+        Ok(())
+    }
 
-        status_modal.set_inner_html(r#"
-            <div class="modal fade" id="status" tabindex="-1" role="dialog" aria-labelledby="statusLabel" aria-hidden="true">
-                <div class="modal-dialog">
-                    <div class="modal-content">
-                        <div class="modal-header" id="statusHeader">
-                        </div>
-                        <div class="modal-body" id="statusBody">
-                        </div>
-                        <div class="modal-footer" id="statusFooter">
-                        </div>
-                    </div>
-                </div>
-            </div>"#);
+    fn on_window_resize(&mut self) -> JsError {
+        let board_size = App::get_window_size();
 
-        document.body()
-            .unwrap()
-            .append_child(&status_modal)?;
+        self.view.resize(self.board_size as f64, board_size as f64);
+        self.board_size = board_size;
+
+        self.status_bar_size()?;
+        self.request_render()?;
 
         Ok(())
     }
 
-    fn on_room_create_response(result: Result<Option<Room>, RoomCreateError>) -> JsError {
-        match result {
-            Ok(room_result) => match room_result {
-                Some(_) => (),
-                None =>
-                    Game::on_room_create_response_success()?,
-            }
+    fn ws_add_event_listener<T>(&self, event: &str, handler: &JsClosure<T>) -> JsError {
+        self.ws
+            .as_deref()
+            .unwrap()
+            .add_event_listener_with_callback(event, handler.as_ref().unchecked_ref())?;
+
+        Ok(())
+    }
+
+    fn ws_add_event_listener_none(&self, event: &str, handler: &JsClosureNone) -> JsError {
+        self.ws
+            .as_deref()
+            .unwrap()
+            .add_event_listener_with_callback(event, handler.as_ref().unchecked_ref())?;
+
+        Ok(())
+    }
+
+    fn ws_send_message(&self, message: &ClientMessage) -> JsError {
+        let message_encoded = bincode::serialize(message)
+            .map_err(|e| JsValue::from_str(
+                    &format!("Could not serialize ClientMessage: {}", e)))?;
+        self.ws
+            .as_ref()
+            .unwrap()
+            .send_with_u8_array(&message_encoded)?;
+
+        Ok(())
+    }
+
+    fn on_ws_message(&mut self, message: &[u8]) -> JsError {
+        let server_message = bincode::deserialize::<ServerMessage>(message)
+            .map_err(|e| JsValue::from_str(
+                    &format!("Could not deserialize ServerMessage: {}", e)))?;
+
+        self.state.apply(&server_message);
+
+        // A staged stone the board no longer allows, say because someone
+        // played there first, goes away along with its confirm button.
+        if let Some(staged) = self.staged {
+            if self.state.check_move(&staged).is_err() {
+                self.set_staged(None)?;
+            }
+        }
+
+        self.describe_for_screen_readers(&server_message)?;
+
+        match server_message {
+            ServerMessage::LoginResponse(result) =>
+                self.on_login_response(result)?,
+            ServerMessage::RoomCreateResponse(result) =>
+                self.on_room_create_response(result)?,
+            ServerMessage::PlaceResponse(result) =>
+                self.on_place_response(result)?,
+            ServerMessage::PlayerAdd(player) =>
+                self.on_player_add(player)?,
+            ServerMessage::PlayerRemove(username) =>
+                self.on_player_remove(username)?,
+            ServerMessage::SpectatorAdd(username) =>
+                self.on_spectator_add(username)?,
+            ServerMessage::SpectatorRemove(username) =>
+                self.on_spectator_remove(username)?,
+            ServerMessage::NextTurn(username) =>
+                self.on_next_turn(username)?,
+            ServerMessage::Chat(message) =>
+                self.on_chat_message_received(message)?,
+            ServerMessage::AlreadyLoggedIn =>
+                self.on_player_already_logged_in()?,
+            ServerMessage::RoomList(rooms) =>
+                self.on_room_list(rooms)?,
+            ServerMessage::SessionToken(token) =>
+                App::on_session_token(token)?,
+        }
+
+        Ok(())
+    }
+
+    /// Mirrors a server message into the accessible board table and the
+    /// live region, since the canvas itself says nothing to screen readers.
+    fn describe_for_screen_readers(&mut self, message: &ServerMessage) -> JsError {
+        match message {
+            ServerMessage::LoginResponse(Ok(_)) | ServerMessage::RoomCreateResponse(Ok(Some(_))) => {
+                self.pending_move = None;
+                self.update_board_table()?;
+            }
+            ServerMessage::PlaceResponse(Ok(action)) => {
+                match &action.0 {
+                    Some((position, Some(stone))) => {
+                        self.announce_pending_move()?;
+
+                        let player = match &action.1 {
+                            Some(player) => player.clone(),
+                            None => self.state.room
+                                .as_ref()
+                                .map(|room| room.current_player.clone())
+                                .unwrap_or_default(),
+                        };
+
+                        self.pending_move = Some(PendingMove {
+                            player,
+                            stone: *stone,
+                            position: *position,
+                            captures: 0,
+                        });
+                    }
+                    Some((_, None)) => {
+                        if let Some(pending_move) = self.pending_move.as_mut() {
+                            pending_move.captures += 1;
+                        }
+                    }
+                    None => {
+                        self.announce_pending_move()?;
+
+                        if let Some(player) = &action.1 {
+                            App::announce(&self.locale.text(Message::Passes(player)))?;
+                        }
+                    }
+                }
+
+                self.update_board_table()?;
+            }
+            ServerMessage::NextTurn(username) => {
+                self.announce_pending_move()?;
+
+                let is_self = self.state.room
+                    .as_ref()
+                    .is_some_and(|room| room.self_player == *username);
+
+                if is_self && ! self.state.spectating {
+                    App::announce(&self.locale.text(Message::YourTurn))?;
+                } else {
+                    App::announce(&self.locale.text(Message::Turn(username)))?;
+                }
+            }
+            ServerMessage::PlayerAdd(player) =>
+                App::announce(&self.locale.text(Message::Joined(&player.username, player.stone)))?,
+            ServerMessage::PlayerRemove(username) =>
+                App::announce(&self.locale.text(Message::Left(username)))?,
+            ServerMessage::SpectatorAdd(username) =>
+                App::announce(&self.locale.text(Message::Spectating(username)))?,
+            ServerMessage::SpectatorRemove(username) =>
+                App::announce(&self.locale.text(Message::StoppedSpectating(username)))?,
+            _ => (),
+        }
+
+        Ok(())
+    }
+
+    fn announce_pending_move(&mut self) -> JsError {
+        let pending_move = match self.pending_move.take() {
+            Some(pending_move) => pending_move,
+            None => return Ok(()),
+        };
+
+        let coordinate = coordinate_label(pending_move.position.x() as usize, pending_move.position.y() as usize);
+
+        App::announce(&self.locale.text(Message::Played {
+            player: &pending_move.player,
+            stone: pending_move.stone,
+            coordinate: &coordinate,
+            captures: pending_move.captures,
+        }))
+    }
+
+    /// Adds a line to the polite live region. Lines are appended rather than
+    /// replaced, so that several announcements in quick succession are all
+    /// read out; only the last few are kept.
+    fn announce(text: &str) -> JsError {
+        let document = web_sys::window()
+            .unwrap()
+            .document()
+            .unwrap();
+
+        let announcer = match document.get_element_by_id("gameAnnouncer") {
+            Some(announcer) => announcer,
+            None => {
+                let announcer = document.create_element("div")?;
+                announcer.set_id("gameAnnouncer");
+                announcer.set_class_name("sr-only");
+                announcer.set_attribute("role", "log")?;
+                announcer.set_attribute("aria-live", "polite")?;
+                announcer.set_attribute("aria-relevant", "additions")?;
+
+                document.body()
+                    .unwrap()
+                    .append_child(&announcer)?;
+
+                announcer
+            }
+        };
+
+        let line = dom::element("p")
+            .text(text)
+            .build()?;
+        announcer.append_child(&line)?;
+
+        while announcer.child_element_count() > 5 {
+            announcer.first_element_child()
+                .unwrap()
+                .remove();
+        }
+
+        Ok(())
+    }
+
+    /// Rebuilds the offscreen table that lets screen readers walk the board
+    /// cell by cell, with the same row and column labels as the canvas.
+    fn update_board_table(&self) -> JsError {
+        let document = web_sys::window()
+            .unwrap()
+            .document()
+            .unwrap();
+
+        let stones = match self.state.room.as_ref() {
+            Some(room) => &room.board.stones,
+            None => return Ok(()),
+        };
+
+        let board_table = match document.get_element_by_id("gameBoardTable") {
+            Some(board_table) => board_table,
+            None => {
+                let board_table = document.create_element("table")?;
+                board_table.set_id("gameBoardTable");
+                board_table.set_class_name("sr-only");
+
+                document.get_element_by_id("gameBoard")
+                    .unwrap()
+                    .insert_adjacent_element("afterend", &board_table)?;
+
+                board_table
+            }
+        };
+
+        board_table.set_inner_html("");
+
+        let caption = document.create_element("caption")?
+            .dyn_into::<HtmlElement>()?;
+        caption.set_inner_text(&self.locale.text(Message::Board));
+        board_table.append_child(&caption)?;
+
+        let header = document.create_element("tr")?;
+        let corner = document.create_element("td")?;
+        header.append_child(&corner)?;
+
+        for x in 0..stones.len() {
+            let column = document.create_element("th")?
+                .dyn_into::<HtmlElement>()?;
+            column.set_attribute("scope", "col")?;
+            column.set_inner_text(&column_label(x));
+            header.append_child(&column)?;
+        }
+
+        board_table.append_child(&header)?;
+
+        for (y, row) in stones.iter().enumerate() {
+            let table_row = document.create_element("tr")?;
+
+            let row_header = document.create_element("th")?
+                .dyn_into::<HtmlElement>()?;
+            row_header.set_attribute("scope", "row")?;
+            row_header.set_inner_text(&(y + 1).to_string());
+            table_row.append_child(&row_header)?;
+
+            for spot in row.iter() {
+                let cell = document.create_element("td")?
+                    .dyn_into::<HtmlElement>()?;
+
+                cell.set_inner_text(&self.locale.text(match spot {
+                    Some(stone) => Message::StoneName(*stone),
+                    None => Message::Empty,
+                }));
+
+                table_row.append_child(&cell)?;
+            }
+
+            board_table.append_child(&table_row)?;
+        }
+
+        Ok(())
+    }
+
+    fn on_room_list(&mut self, rooms: Vec<RoomSummary>) -> JsError {
+        let document = web_sys::window()
+            .unwrap()
+            .document()
+            .unwrap();
+
+        let lobby = document.get_element_by_id("loginLobby")
+            .unwrap()
+            .dyn_into::<HtmlElement>()?;
+
+        let lobby_rooms = document.get_element_by_id("loginLobbyRooms")
+            .unwrap();
+        lobby_rooms.set_inner_html("");
+
+        if rooms.is_empty() {
+            let empty_row = document.create_element("tr")?;
+            let empty_cell = document.create_element("td")?
+                .dyn_into::<HtmlElement>()?;
+            empty_cell.set_attribute("colspan", "5")?;
+            empty_cell.set_inner_text(&self.locale.text(Message::NoRooms));
+            empty_row.append_child(&empty_cell)?;
+            lobby_rooms.append_child(&empty_row)?;
+        }
+
+        for room in rooms.into_iter() {
+            let phase = self.locale.text(Message::GamePhase(room.phase));
+
+            let mut join_button = dom::element("button")
+                .class("btn btn-primary btn-sm")
+                .attribute("type", "button")
+                .attribute("data-room", room.name.clone())
+                .text(self.locale.text(Message::Join));
+            if room.players >= room.capacity {
+                join_button = join_button.attribute("disabled", "");
+            }
+
+            let watch_button = dom::element("button")
+                .class("btn btn-secondary btn-sm")
+                .attribute("type", "button")
+                .attribute("data-room", room.name.clone())
+                .attribute("data-spectate", "")
+                .text(self.locale.text(Message::Watch));
+
+            let row = dom::element("tr")
+                .child(dom::element("td").text(room.name))
+                .child(dom::element("td").text(format!("{}x{}", room.width, room.height)))
+                .child(dom::element("td").text(format!("{}/{}", room.players, room.capacity)))
+                .child(dom::element("td").text(phase))
+                .child(dom::element("td")
+                    .child(join_button)
+                    .child(watch_button))
+                .build()?;
+
+            lobby_rooms.append_child(&row)?;
+        }
+
+        lobby.set_hidden(false);
+
+        Ok(())
+    }
+
+    /// Adds the room lobby under the login form, hidden until the server
+    /// sends a list, and joins or watches rooms picked in it. One listener
+    /// on the room table serves every row, so refreshing the list adds no
+    /// closures; the buttons carry the room name and whether they watch.
+    fn set_lobby(dispatcher: &Dispatcher, locale: Locale) -> JsError {
+        let document = web_sys::window()
+            .unwrap()
+            .document()
+            .unwrap();
+
+        let lobby = dom::element("div")
+            .id("loginLobby")
+            .child(dom::element("h4").text(locale.text(Message::Rooms)))
+            .child(dom::element("table")
+                .class("table table-sm table-hover")
+                .child(dom::element("thead")
+                    .child(dom::element("tr")
+                        .child(dom::element("th").text(locale.text(Message::Room)))
+                        .child(dom::element("th").text(locale.text(Message::Board)))
+                        .child(dom::element("th").text(locale.text(Message::Players)))
+                        .child(dom::element("th").text(locale.text(Message::Phase)))
+                        .child(dom::element("th"))))
+                .child(dom::element("tbody")
+                    .id("loginLobbyRooms")))
+            .build()?
+            .dyn_into::<HtmlElement>()?;
+        lobby.set_hidden(true);
+
+        document.get_element_by_id("login")
+            .unwrap()
+            .append_child(&lobby)?;
+
+        let lobby_rooms = document.get_element_by_id("loginLobbyRooms")
+            .unwrap();
+
+        let dispatcher = dispatcher.clone();
+        let on_click = Closure::wrap(Box::new(move |e: web_sys::Event| {
+            let button = e.target()
+                .and_then(|target| target.dyn_into::<web_sys::Element>().ok())
+                .and_then(|target| target.closest("button[data-room]:not([disabled])").ok().flatten());
+
+            if let Some(button) = button {
+                let room_name = button.get_attribute("data-room")
+                    .unwrap_or_default();
+
+                dispatcher.dispatch(Event::LobbyJoin(room_name, button.has_attribute("data-spectate")));
+            }
+
+            Ok::<(), JsValue>(())
+        }) as Box<dyn FnMut(web_sys::Event) -> JsError>);
+
+        lobby_rooms.add_event_listener_with_callback("click", on_click.as_ref().unchecked_ref())?;
+
+        on_click.forget();
+
+        Ok(())
+    }
+
+    fn on_lobby_room_click(&mut self, room_name: &str, spectate: bool) -> JsError {
+        let document = web_sys::window()
+            .unwrap()
+            .document()
+            .unwrap();
+
+        let login_room = document.get_element_by_id("loginRoom")
+            .unwrap()
+            .dyn_into::<HtmlInputElement>()?;
+        login_room.set_value(room_name);
+
+        self.state.spectating = spectate;
+
+        self.connect()?;
+
+        Ok(())
+    }
+
+    fn on_login_response(&mut self, result: Result<Room, LoginError>) -> JsError {
+        App::login_form_reset()?;
+
+        match result {
+            Ok(room) => self.on_login_response_success(room)?,
+
+            Err(login_error) => {
+                let message = self.locale.text(Message::LoginError(&login_error));
+
+                match login_error {
+                    LoginError::RoomFull | LoginError::RoomNameTooLong =>
+                        App::on_login_response_room_error(&message)?,
+                    LoginError::UsernameTaken | LoginError::UsernameTooLong =>
+                        App::on_login_response_username_error(&message)?,
+                    LoginError::RoomDoesNotExist(room_name) =>
+                        self.on_login_response_room_does_not_exist(room_name, &message)?,
+                    LoginError::SessionExpired =>
+                        self.on_login_response_session_expired()?,
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn on_login_response_success(&mut self, room: Room) -> JsError {
+        let current_player = room.current_player;
+
+        let document = web_sys::window()
+            .unwrap()
+            .document()
+            .unwrap();
+
+        let login_form = document.get_element_by_id("login")
+            .unwrap()
+            .dyn_into::<HtmlElement>()?;
+
+        login_form.set_hidden(true);
+
+        let room_el = document.get_element_by_id("loginRoom")
+            .unwrap()
+            .dyn_into::<HtmlInputElement>()?;
+
+
+        self.status_bar_size()?;
+        self.status_bar_header(room_el.value())?;
+
+        self.takeover_pending = false;
+
+        self.tab_channel_post("claim")?;
+
+        let pass_button = document.get_element_by_id("playPass")
+            .unwrap()
+            .dyn_into::<HtmlElement>()?;
+
+        pass_button.set_hidden(self.state.spectating);
+
+        self.players.highlight(&current_player, Highlight::Turn)?;
+
+        self.load_sound_settings()?;
+        
+        self.request_render()?;
+
+        Ok(())
+    }
+
+    fn on_session_token(token: String) -> JsError {
+        let (_, room_name, username) = App::login_seat()?;
+        App::session_token_store(&username, &room_name, Some(&token))?;
+
+        Ok(())
+    }
+
+    fn on_login_response_session_expired(&mut self) -> JsError {
+        let (_, room_name, username) = App::login_seat()?;
+        App::session_token_store(&username, &room_name, None)?;
+
+        // Retry as a fresh login, which succeeds if the seat is free by now
+        self.ws_send_message(&ClientMessage::Login(username, room_name))?;
+
+        Ok(())
+    }
+
+    /// Marks the room field as invalid, explaining why with `message`.
+    fn on_login_response_room_error(message: &str) -> JsError {
+        let document = web_sys::window()
+            .unwrap()
+            .document()
+            .unwrap();
+
+        let login_room = document.get_element_by_id("loginRoom")
+            .unwrap();
+
+        login_room.set_class_name("form-control is-invalid");
+
+        let login_room_error = document
+            .get_element_by_id("loginRoomError");
+
+        if let None = login_room_error {
+            let login_room_error = document.create_element("div")?
+                .dyn_into::<HtmlElement>()?;
+            login_room_error.set_id("loginRoomError");
+            login_room_error.set_class_name("invalid-feedback");
+            login_room_error.set_inner_text(message);
+
+            let login_room_form = document.get_element_by_id("loginRoomForm")
+                .unwrap();
+
+            login_room_form.append_child(&login_room_error)?;
+        } else {
+            login_room_error
+                .unwrap()
+                .dyn_into::<HtmlElement>()?
+                .set_inner_text(message);
+        }
+
+        Ok(())
+    }
+
+    /// Marks the username field as invalid, explaining why with `message`.
+    fn on_login_response_username_error(message: &str) -> JsError {
+        let document = web_sys::window()
+            .unwrap()
+            .document()
+            .unwrap();
+
+        let login_username = document.get_element_by_id("loginUsername")
+            .unwrap();
+
+        login_username.set_class_name("form-control is-invalid");
+
+        let login_username_error = document
+            .get_element_by_id("loginUsernameError");
+
+        if let None = login_username_error {
+            let login_username_error = document.create_element("div")?
+                .dyn_into::<HtmlElement>()?;
+            login_username_error.set_id("loginUsernameError");
+            login_username_error.set_class_name("invalid-feedback");
+            login_username_error.set_inner_text(message);
+
+            let login_username_form = document.get_element_by_id("loginUsernameForm")
+                .unwrap();
+
+            login_username_form.append_child(&login_username_error)?;
+        } else {
+            login_username_error
+                .unwrap()
+                .dyn_into::<HtmlElement>()?
+                .set_inner_text(message);
+        }
+
+        Ok(())
+    }
+
+    fn on_login_response_room_does_not_exist(&mut self, room_name: String, message: &str) -> JsError {
+        let locale = self.locale;
+
+        let modal = Modal::new(locale.text(Message::CreateRoom))
+            .text(message)
+            .field("roomName", locale.text(Message::RoomName), room_name)
+            .field("boardLength", locale.text(Message::BoardLength), "")
+            .field("roomCapacity", locale.text(Message::RoomCapacity), "")
+            .action(locale.text(Message::Quit), Event::StatusQuit)
+            .primary(locale.text(Message::Create), Event::RoomCreateSubmit);
+
+        self.open_modal(modal)?;
+
+        App::login_form_reset()?;
+
+        Ok(())
+    }
+
+    /// Shows `modal`, closing any other first so that it hands focus back
+    /// before the new one takes it.
+    fn open_modal(&mut self, modal: Modal) -> JsError {
+        self.modal = None;
+        self.modal = Some(modal.open(&self.dispatcher())?);
+
+        Ok(())
+    }
+
+    fn on_status_quit(&mut self) -> JsError {
+        self.modal = None;
+
+        if let Some(ws) = self.ws.take() {
+            ws.close()?;
+        }
+
+        Ok(())
+    }
+
+    fn on_room_create_button_submit(&mut self) -> JsError {
+        let modal = match self.modal.as_ref() {
+            Some(modal) => modal,
+            None => return Ok(()),
+        };
+
+        let room_name = modal.validate("roomName",
+                                       |value| Some(value.to_string()).filter(|value| ! value.is_empty()),
+                                       &self.locale.text(Message::MissingRoomName))?;
+        let board_size = modal.validate("boardLength",
+                                        |value| value.parse::<u8>().ok(),
+                                        &self.locale.text(Message::InvalidDimensions))?;
+        let capacity = modal.validate("roomCapacity",
+                                      |value| value.parse::<u8>().ok(),
+                                      &self.locale.text(Message::InvalidCapacity))?;
+
+        let (room_name, board_size, capacity) = match (room_name, board_size, capacity) {
+            (Some(room_name), Some(board_size), Some(capacity)) => (room_name, board_size, capacity),
+            _ => return Ok(()),
+        };
+
+        web_sys::window()
+            .unwrap()
+            .document()
+            .unwrap()
+            .get_element_by_id("loginRoom")
+            .unwrap()
+            .dyn_into::<HtmlInputElement>()?
+            .set_value(&room_name);
+
+        let create_room = ClientMessage::RoomCreate(room_name, capacity, board_size, board_size);
+
+        self.ws_send_message(&create_room)?;
+        Ok(())
+    }
+
+    fn on_room_create_response(&mut self, result: Result<Option<Room>, RoomCreateError>) -> JsError {
+        match result {
+            Ok(room_result) => match room_result {
+                Some(room) =>
+                    self.on_room_create_response_success_joined(room)?,
+                None =>
+                    self.on_room_create_response_success()?,
+            }
+
+            Err(room_create_error) =>
+                self.room_create_name_error(&self.locale.text(Message::RoomCreateError(&room_create_error)))?,
+        }
+
+        Ok(())
+    }
+
+    fn on_room_create_response_success(&mut self) -> JsError {
+        let document = web_sys::window()
+            .unwrap()
+            .document()
+            .unwrap();
+
+        self.modal = None;
+
+        let username = document.get_element_by_id("loginUsername")
+            .unwrap()
+            .dyn_into::<HtmlInputElement>()?;
+        let username = username.value();
+        
+        let room = document.get_element_by_id("loginRoom")
+            .unwrap()
+            .dyn_into::<HtmlInputElement>()?;
+        let room = room.value();
+
+        let login_message = self.login_message(username, room);
+
+        self.ws_send_message(&login_message)?;
+
+        Ok(())
+    }
+
+    fn on_room_create_response_success_joined(&mut self, room: Room) -> JsError {
+        self.modal = None;
+
+        App::login_form_reset()?;
+        self.on_login_response_success(room)?;
+
+        Ok(())
+    }
+
+    fn room_create_name_error(&self, message: &str) -> JsError {
+        if let Some(modal) = self.modal.as_ref() {
+            modal.set_error("roomName", Some(message))?;
+            modal.focus("roomName")?;
+        }
+
+        Ok(())
+    }
+
+    fn on_place_response(&mut self, result: Result<Move<u8>, InvalidMove>) -> JsError {
+        match result {
+            Err(_invalid_move) => {
+                // UNHANDLED
+            }
+
+            Ok(action) => self.on_place_response_success(action)?
+        }
+        Ok(())
+    }
+
+    fn on_place_response_success(&mut self, action: Move<u8>) -> JsError {
+        match &action.0 {
+            Some((_, Some(_))) => {
+                self.audio.play(Sound::Place)?;
+                self.capture_sounded = false;
+            }
+            // Every captured stone arrives on its own; one sound covers them.
+            Some((_, None)) => {
+                if ! self.capture_sounded {
+                    self.audio.play(Sound::Capture)?;
+                    self.capture_sounded = true;
+                }
+            }
+            None =>
+                self.audio.play(Sound::Pass)?,
+        }
+
+        match action.0 {
+            Some(_) => {
+                self.request_render()?;
+
+                self.players.clear_highlights()?;
+            }
+
+            None => {
+                if let Some(username) = action.1.as_ref() {
+                    self.players.highlight(username, Highlight::Passed)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn on_player_add(&mut self, player: Player) -> JsError {
+        let document = web_sys::window()
+            .unwrap()
+            .document()
+            .unwrap();
+
+        let player_list = document.get_element_by_id("gameStatusRoomPlayers")
+            .unwrap();
+
+        self.players.add(&player_list, &player.username, Some(player.stone), self.locale)?;
+
+        Ok(())
+    }
+
+    fn on_player_remove(&mut self, username: String) -> JsError {
+        self.players.remove(&username);
+
+        Ok(())
+    }
+
+    fn spectator_list(&self) -> JsResult<web_sys::Element> {
+        let document = web_sys::window()
+            .unwrap()
+            .document()
+            .unwrap();
+
+        if let Some(spectator_list) = document.get_element_by_id("gameStatusRoomSpectators") {
+            return Ok(spectator_list);
+        }
+
+        let spectator_list = document.create_element("table")?;
+        spectator_list.set_id("gameStatusRoomSpectators");
+        spectator_list.set_class_name("table table-sm");
+
+        let spectator_title = document.create_element("caption")?
+            .dyn_into::<HtmlElement>()?;
+        spectator_title.set_inner_text(&self.locale.text(Message::Spectators));
+        spectator_list.append_child(&spectator_title)?;
+
+        let game_status_room = document.get_element_by_id("gameStatusRoom")
+            .unwrap();
+        game_status_room.append_child(&spectator_list)?;
+
+        Ok(spectator_list)
+    }
+
+    fn on_spectator_add(&mut self, username: String) -> JsError {
+        let spectator_list = self.spectator_list()?;
+
+        self.spectators.add(&spectator_list, &username, None, self.locale)?;
+
+        Ok(())
+    }
+
+    fn on_spectator_remove(&mut self, username: String) -> JsError {
+        self.spectators.remove(&username);
+
+        Ok(())
+    }
+
+    fn on_player_already_logged_in(&mut self) -> JsError {
+        let (_, room_name, username) = App::login_seat()?;
+
+        let locale = self.locale;
+
+        let modal = Modal::new(locale.text(Message::AlreadyLoggedIn))
+            .text(locale.text(Message::AlreadyPlaying {
+                username: &username,
+                room: &room_name,
+            }))
+            .action(locale.text(Message::Quit), Event::StatusQuit)
+            .primary(locale.text(Message::UseThisTab), Event::TakeoverAccept);
+
+        self.open_modal(modal)?;
+
+        Ok(())
+    }
+
+    fn on_takeover(&mut self) -> JsError {
+        self.modal = None;
+
+        self.takeover_pending = true;
+
+        self.tab_channel_post("claim")?;
+
+        // Another device may hold the seat, in which case no tab answers the
+        // claim and logging in again would only be refused again.
+        let dispatcher = self.dispatcher();
+        let on_timeout = Closure::once_into_js(move || {
+            dispatcher.dispatch(Event::TakeoverTimeout);
+        });
+
+        web_sys::window()
+            .unwrap()
+            .set_timeout_with_callback_and_timeout_and_arguments_0(on_timeout.unchecked_ref(), 1000)?;
+
+        Ok(())
+    }
+
+    fn on_takeover_released(&mut self) -> JsError {
+        if self.takeover_pending && self.ws.is_some() {
+            self.takeover_pending = false;
+
+            let (_, room_name, username) = App::login_seat()?;
+            let login_message = self.login_message(username, room_name);
+            self.ws_send_message(&login_message)?;
+        }
+
+        Ok(())
+    }
+
+    fn on_takeover_timeout(&mut self) -> JsError {
+        if ! self.takeover_pending {
+            return Ok(());
+        }
+
+        self.takeover_pending = false;
+
+        let (_, room_name, username) = App::login_seat()?;
+
+        let locale = self.locale;
+
+        let modal = Modal::new(locale.text(Message::PlayingElsewhere))
+            .text(locale.text(Message::PlayingElsewhereDescription {
+                username: &username,
+                room: &room_name,
+            }))
+            .primary(locale.text(Message::Quit), Event::StatusQuit);
+
+        self.open_modal(modal)?;
+
+        Ok(())
+    }
+
+    fn on_session_taken_over(&mut self) -> JsError {
+        // The seat is only free once the server has seen the socket close,
+        // so the claiming tab hears about it from on_ws_close.
+        match self.ws.as_ref() {
+            Some(ws) => {
+                self.release_pending = true;
+                ws.close()?;
+            }
+            None => self.tab_channel_post("released")?,
+        }
+
+        let locale = self.locale;
+
+        let modal = Modal::new(locale.text(Message::SessionMoved))
+            .text(locale.text(Message::SessionMovedDescription))
+            .action(locale.text(Message::Close), Event::ModalClose)
+            .dismiss(Event::ModalClose);
+
+        self.open_modal(modal)?;
+
+        Ok(())
+    }
+
+    fn set_tab_channel(dispatcher: &Dispatcher) -> JsResult<Option<BroadcastChannel>> {
+        let tab_channel = match BroadcastChannel::new("rengo-client") {
+            Ok(tab_channel) => tab_channel,
+            // Tab coordination is best effort on browsers without BroadcastChannel
+            Err(_) => return Ok(None),
+        };
+
+        let dispatcher = dispatcher.clone();
+        let on_tab_message = Closure::wrap(Box::new(move |e: MessageEvent| {
+            let data = js_sys::Array::from(&e.data());
+            let kind = data.get(0).as_string().unwrap_or_default();
+            let seat = (
+                data.get(1).as_string().unwrap_or_default(),
+                data.get(2).as_string().unwrap_or_default(),
+                data.get(3).as_string().unwrap_or_default(),
+            );
+
+            dispatcher.dispatch(Event::TabMessage(kind, seat));
+
+            Ok::<(), JsValue>(())
+        }) as Box<dyn FnMut(MessageEvent) -> JsError>);
+
+        tab_channel.set_onmessage(Some(on_tab_message.as_ref().unchecked_ref()));
+
+        on_tab_message.forget();
+
+        Ok(Some(tab_channel))
+    }
+
+    fn on_tab_message(&mut self, kind: &str, seat: (String, String, String)) -> JsError {
+        if seat != App::login_seat()? {
+            return Ok(());
+        }
+
+        let seated = self.state.room.is_some();
+
+        match kind {
+            "claim" if seated =>
+                self.on_session_taken_over()?,
+            "released" =>
+                self.on_takeover_released()?,
+            _ => (),
+        }
+
+        Ok(())
+    }
+
+    fn login_seat() -> JsResult<(String, String, String)> {
+        let document = web_sys::window()
+            .unwrap()
+            .document()
+            .unwrap();
+
+        let login_server = document.get_element_by_id("loginServer")
+            .unwrap()
+            .dyn_into::<HtmlInputElement>()?;
+        let login_room = document.get_element_by_id("loginRoom")
+            .unwrap()
+            .dyn_into::<HtmlInputElement>()?;
+        let login_username = document.get_element_by_id("loginUsername")
+            .unwrap()
+            .dyn_into::<HtmlInputElement>()?;
+
+        Ok((login_server.value(), login_room.value(), login_username.value()))
+    }
+
+    fn tab_channel_post(&self, kind: &str) -> JsError {
+        let (server, room_name, username) = App::login_seat()?;
+
+        let message = js_sys::Array::new();
+        message.push(&JsValue::from_str(kind));
+        message.push(&JsValue::from_str(&server));
+        message.push(&JsValue::from_str(&room_name));
+        message.push(&JsValue::from_str(&username));
+
+        if let Some(tab_channel) = self.tab_channel.as_ref() {
+            tab_channel.post_message(&message)?;
+        }
+
+        Ok(())
+    }
+
+    fn login_form_reset() -> JsError {
+        let document = web_sys::window()
+            .unwrap()
+            .document()
+            .unwrap();
+
+        let login_username = document.get_element_by_id("loginUsername")
+            .unwrap();
+        let login_room = document.get_element_by_id("loginRoom")
+            .unwrap();
+
+        login_username.set_class_name("form-control");
+        login_room.set_class_name("form-control");
+
+        if let Some(login_username_error) = document.get_element_by_id("loginUsernameError") {
+            login_username_error.remove();
+        }
+
+        if let Some(login_room_error) = document.get_element_by_id("loginRoomError") {
+            login_room_error.remove();
+        }
+
+        Ok(())
+    }
+
+    fn status_bar_size(&mut self) -> JsError {
+        let document = web_sys::window()
+            .unwrap()
+            .document()
+            .unwrap();
+
+        let board_status = document.get_element_by_id("gameStatus")
+            .unwrap()
+            .dyn_into::<HtmlElement>()?;
+
+        let game_status_room = document.get_element_by_id("gameStatusRoom")
+            .unwrap()
+            .dyn_into::<HtmlElement>()?;
+
+        let game_status_chat = document.get_element_by_id("gameStatusChat")
+            .unwrap()
+            .dyn_into::<HtmlElement>()?;
+
+        let board_size = self.board_size;
+        let status_height = (board_size as f64 * 9_f64 / 10_f64) as u32;
+        let status_width = (board_size as f64 / 3_f64) as u32;
+
+        board_status.style()
+            .set_property("width", &format!("{}px", status_width))?;
+        let partial_height = (status_height as f64 * 9_f64 / 20_f64) as u32;
+        game_status_room.style()
+            .set_property("height", &format!("{}px", partial_height))?;
+        game_status_chat.style()
+            .set_property("height", &format!("{}px", partial_height))?;
+
+        let pass_button = document.get_element_by_id("playPass")
+            .unwrap()
+            .dyn_into::<HtmlElement>()?;
+        let quit_button = document.get_element_by_id("playQuit")
+            .unwrap()
+            .dyn_into::<HtmlElement>()?;
+
+        let chat_box = document.get_element_by_id("gameStatusChatMessages")
+            .unwrap()
+            .dyn_into::<HtmlElement>()?;
+
+        let chat_height = (partial_height as f64 * (10_f64 / 15_f64)) as u32;
+
+        let game_status_chat_input = document.get_element_by_id("gameStatusChatInput")
+            .unwrap()
+            .dyn_into::<HtmlElement>()?;
+
+        game_status_chat_input.style()
+            .set_property("margin-left", &format!("8%"))?;
+
+        pass_button.style()
+            .set_property("margin-top", &format!("8vh"))?;
+        quit_button.style()
+            .set_property("margin-top", &format!("8vh"))?;
+        
+        chat_box.style()
+            .set_property("height", &format!("{}px", chat_height))?;
+
+        Ok(())
+    }
+
+    fn status_bar_header(&mut self, room_name: String) -> JsError {
+        let document = web_sys::window()
+            .unwrap()
+            .document()
+            .unwrap();
+
+        let players = self.state.room
+            .as_ref()
+            .unwrap()
+            .players
+            .clone();
+
+        let spectators = self.state.room
+            .as_ref()
+            .unwrap()
+            .spectators
+            .clone();
+
+        let status_bar_title = document.get_element_by_id("gameStatusRoomTitle")
+            .unwrap();
+
+        status_bar_title.set_text_content(Some(&self.locale.text(Message::RoomTitle(&room_name))));
+
+        let player_list = document.get_element_by_id("gameStatusRoomPlayers")
+            .unwrap();
+        
+        for (username, val) in players.iter() {
+            self.players.add(&player_list, username, Some(val.stone), self.locale)?;
+        }
+
+        for username in spectators.into_iter() {
+            self.on_spectator_add(username)?;
+        }
+
+        Ok(())
+    }
+
+    fn on_mouse_click(&mut self, x: i32, y: i32) -> JsError {
+        // The end of a pan is not a click.
+        if self.drag.take().is_some_and(|drag| drag.panning) {
+            return Ok(());
+        }
+
+        if let Some(position) = self.board_position(x, y) {
+            self.choose(position, self.confirm_moves)?;
+        }
+
+        Ok(())
+    }
+
+    /// Plays at `position`, or only stages a translucent stone there when
+    /// `confirm` is set. Choosing the staged point a second time sends it.
+    fn choose(&mut self, position: Position<u32>, confirm: bool) -> JsError {
+        if self.state.check_move(&position).is_err() {
+            return Ok(());
+        }
+
+        if confirm && self.staged != Some(position) {
+            return self.set_staged(Some(position));
+        }
+
+        self.set_staged(None)?;
+        self.place(&position)
+    }
+
+    /// Intersection under the canvas point (`x`, `y`), taking the current
+    /// zoom, pan and orientation into account.
+    fn board_position(&self, x: i32, y: i32) -> Option<Position<u32>> {
+        let geometry = self.geometry?;
+        let (board_x, board_y) = self.view.to_board(x as f64, y as f64);
+
+        let position = geometry.piece_position(board_x.round() as i32, board_y.round() as i32)?;
+        let (x, y) = self.orientation.to_board(position.x() as usize, position.y() as usize, geometry.side_length);
+
+        Some(Position(x as u32, y as u32))
+    }
+
+    /// Sends a stone to the server, unless the move is already known to be
+    /// illegal.
+    fn place(&mut self, position: &Position<u32>) -> JsError {
+        if self.state.check_move(position).is_ok() {
+            let message = ClientMessage::Place(Some(Position(position.x() as u8, position.y() as u8)));
+            self.ws_send_message(&message)?;
+        }
+
+        Ok(())
+    }
+
+    fn on_next_turn(&mut self, username: String) -> JsError {
+        self.players.highlight_only(&username, Highlight::Turn)?;
+
+        if self.state.is_self_turn() {
+            self.audio.play(Sound::YourTurn)?;
+            self.alert_turn()?;
+        } else {
+            self.turn_alert = None;
+        }
+
+        Ok(())
+    }
+
+    /// Asks for permission to notify about turns. Browsers only show the
+    /// prompt from a user gesture, such as the login button being clicked.
+    fn request_notification_permission() -> JsError {
+        // Notifications are not available everywhere, e.g. on iOS browsers
+        // outside a home screen app.
+        let supported = js_sys::Reflect::has(&web_sys::window().unwrap(), &JsValue::from_str("Notification"))?;
+
+        if supported && Notification::permission() == NotificationPermission::Default {
+            let _ = Notification::request_permission()?;
+        }
+
+        Ok(())
+    }
+
+    /// Lets a player in another tab or window know that it is their turn,
+    /// with a notification if allowed and by flashing the tab title until
+    /// the page has focus again.
+    fn alert_turn(&mut self) -> JsError {
+        let window = web_sys::window()
+            .unwrap();
+        let document = window.document()
+            .unwrap();
+
+        if ! document.hidden() && document.has_focus()? {
+            return Ok(());
+        }
+
+        let supported = js_sys::Reflect::has(&window, &JsValue::from_str("Notification"))?;
+
+        if supported && Notification::permission() == NotificationPermission::Granted {
+            let room_name = document.get_element_by_id("loginRoom")
+                .unwrap()
+                .dyn_into::<HtmlInputElement>()?
+                .value();
+
+            let options = NotificationOptions::new();
+            options.set_body(&self.locale.text(Message::YourTurnIn(&room_name)));
+            // Replaces the previous turn's notification instead of piling up
+            options.set_tag("rengo-turn");
+
+            let notification = Notification::new_with_options(&self.locale.text(Message::YourTurn), &options)?;
+
+            let on_click = Closure::once_into_js(move || {
+                let _ = web_sys::window()
+                    .unwrap()
+                    .focus();
+            });
+
+            notification.set_onclick(Some(on_click.unchecked_ref()));
+        }
+
+        if self.turn_alert.is_none() {
+            let on_tick = self.dispatcher().callback(Event::TitleFlash);
+
+            let interval = window.set_interval_with_callback_and_timeout_and_arguments_0(
+                on_tick.as_ref().unchecked_ref(), 1000)?;
+
+            self.turn_alert = Some(TurnAlert {
+                interval,
+                title: document.title(),
+                _on_tick: on_tick,
+            });
+        }
+
+        Ok(())
+    }
+
+    fn on_title_flash(&mut self) -> JsError {
+        let document = web_sys::window()
+            .unwrap()
+            .document()
+            .unwrap();
+
+        if let Some(turn_alert) = self.turn_alert.as_ref() {
+            if document.title() == turn_alert.title {
+                document.set_title(&self.locale.text(Message::YourTurnTitle(&turn_alert.title)));
+            } else {
+                document.set_title(&turn_alert.title);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn set_window_focus(dispatcher: &Dispatcher) -> JsError {
+        let window = web_sys::window()
+            .unwrap();
+
+        let on_focus = dispatcher.callback(Event::WindowFocus);
+
+        window.set_onfocus(Some(on_focus.as_ref().unchecked_ref()));
 
-            Err(room_create_error) => match room_create_error {
-                RoomCreateError::RoomNameTooLong =>
-                    Game::on_room_create_response_room_name_too_long()?,
-                RoomCreateError::RoomNameTaken =>
-                    Game::on_room_create_response_room_name_taken()?,
-            }
-        }
+        on_focus.forget();
 
         Ok(())
     }
 
-    fn on_room_create_response_success() -> JsError {
+    /// Appends a chat message to the pane. Sender and text come from the
+    /// server as separate fields and are only ever inserted as text, through
+    /// `chat::line`; system messages have no sender and are set apart.
+    fn on_chat_message_received(&mut self, message: ChatMessage) -> JsError {
         let document = web_sys::window()
             .unwrap()
             .document()
             .unwrap();
 
-        let show_modal = js_sys::Function::new_with_args("name", "$(name).modal('hide')");
-        show_modal.call1(&JsValue::null(), &JsValue::from_str("#status"))?;
+        let own_message = self.state.room
+            .as_ref()
+            .is_some_and(|room| message.sender.as_ref() == Some(&room.self_player));
 
-        Game::status_modal_reset()?;
+        if ! own_message && message.channel != ChatChannel::System {
+            self.audio.play(Sound::Chat)?;
+        }
 
-        let username = document.get_element_by_id("loginUsername")
-            .unwrap()
-            .dyn_into::<HtmlInputElement>()?;
-        let username = username.value();
-        
-        let room = document.get_element_by_id("loginRoom")
+        let chat_area = document.get_element_by_id("gameStatusChatMessages")
             .unwrap()
-            .dyn_into::<HtmlInputElement>()?;
-        let room = room.value();
+            .dyn_into::<HtmlElement>()?;
 
-        let login_message = ClientMessage::Login(username, room);
+        let date = js_sys::Date::new(&JsValue::from_f64(message.timestamp as f64));
+        let time = format!("{:02}:{:02}", date.get_hours(), date.get_minutes());
+        let datetime = String::from(date.to_iso_string());
 
-        Game::ws_send_message(&login_message)?;
+        // Coordinates become buttons that point the intersection out on the
+        // board; see set_chat_coordinates.
+        let new_message = chat::line(&message, &time, &datetime, self.side_length().unwrap_or(0), self.locale)
+            .build()?;
 
-        Ok(())
-    }
+        chat_area.append_child(&new_message)?;
 
-    fn on_room_create_response_room_name_too_long() -> JsError {
-        // UNHANDLED
-        Ok(())
-    }
+        chat_area.set_scroll_top(chat_area.scroll_height());
 
-    fn on_room_create_response_room_name_taken() -> JsError {
-        // UNHANDLED
         Ok(())
     }
 
-    fn on_place_response(result: Result<Move<u8>, InvalidMove>) -> JsError {
-        match result {
-            Err(_invalid_move) => {
-                // UNHANDLED
-            }
-
-            Ok(action) => Game::on_place_response_success(action)?
+    fn on_touch_down(&mut self, id: i32, x: i32, y: i32) -> JsError {
+        self.touches.retain(|(other, _)| *other != id);
+        self.touches.push((id, Position(x, y)));
+
+        if self.touches.len() > 1 {
+            // A second finger turns the gesture into a pinch, which never
+            // places anything.
+            self.pinching = true;
+            self.touching = false;
+            self.preview = None;
+        } else if ! self.pinching {
+            self.touching = true;
+            self.preview = Some(Position(x, y));
         }
+
+        // The staged stone stays put underneath, so that tapping it again
+        // can confirm it.
+        self.request_render()?;
+
         Ok(())
     }
 
-    fn on_place_response_success(action: Move<u8>) -> JsError {
-        let document = web_sys::window()
-            .unwrap()
-            .document()
-            .unwrap();
+    fn on_touch_move(&mut self, id: i32, x: i32, y: i32) -> JsError {
+        let index = match self.touches.iter().position(|(other, _)| *other == id) {
+            Some(index) => index,
+            None => return Ok(()),
+        };
 
-        match action.0 {
-            Some(stone_move) => {
-                Game::move_stone(stone_move.0, stone_move.1)?;
+        if self.pinching && index < 2 && self.touches.len() >= 2 {
+            let before = (self.touches[0].1, self.touches[1].1);
+            self.touches[index].1 = Position(x, y);
+            let after = (self.touches[0].1, self.touches[1].1);
 
-                let player_table = document.get_element_by_id("gameStatusRoomPlayers")
-                    .unwrap()
-                    .dyn_into::<HtmlElement>()?;
+            self.on_pinch(before, after)?;
+        } else {
+            self.touches[index].1 = Position(x, y);
+        }
 
-                let players = player_table.children();
+        if self.touching {
+            self.preview = Some(Position(x, y));
+            self.request_render()?;
+        }
 
-                for player_i in 0..players.length() {
-                    let player = players.item(player_i)
-                        .unwrap()
-                        .dyn_into::<HtmlElement>()?;
+        Ok(())
+    }
 
-                    player.style()
-                        .set_property("background-color", "#ffffff")?;
-                }
-            }
+    fn on_touch_up(&mut self, id: i32, x: i32, y: i32) -> JsError {
+        self.touches.retain(|(other, _)| *other != id);
 
-            None => {
-                let username = action.1.unwrap();
+        if self.pinching {
+            self.pinching = ! self.touches.is_empty();
+            return Ok(());
+        }
 
-                let username_el = document.get_element_by_id(&format!("player-{}", username))
-                    .unwrap()
-                    .dyn_into::<HtmlElement>()?;
+        if ! self.touching {
+            return Ok(());
+        }
 
-                username_el.style()
-                    .set_property("background-color", "red")?;
-            }
+        self.touching = false;
+        self.preview = None;
+
+        // Touch only ever stages, whatever the confirm setting: a stray
+        // second tap must not place, so the stone goes down through the
+        // confirm button alone.
+        match self.board_position(x, y) {
+            Some(position) if self.state.check_move(&position).is_ok() =>
+                self.set_staged(Some(position))?,
+            _ => self.set_staged(None)?,
         }
+
         Ok(())
     }
 
-    fn move_stone(position: Position<u8>, stone: Option<Stone>) -> JsError {
-        STATE.lock()
-            .unwrap()
-            .borrow_mut()
-            .room
-            .as_mut()
-            .unwrap()
-            .board
-            .stones[position.y() as usize][position.x() as usize] = stone;
+    fn on_touch_cancel(&mut self, id: i32) -> JsError {
+        self.touches.retain(|(other, _)| *other != id);
 
-        Game::render()?;
+        if self.touches.is_empty() {
+            self.pinching = false;
+            self.touching = false;
+            self.preview = None;
+            self.set_staged(None)?;
+        }
 
         Ok(())
     }
 
-    fn on_player_add(player: Player) -> JsError {
-        let document = web_sys::window()
-            .unwrap()
-            .document()
-            .unwrap();
-
-        let player_list = document.get_element_by_id("gameStatusRoomPlayers")
-            .unwrap();
-        
-        let new_player = document.create_element("tr")?;
-
-        let mut stone_color = "Black";
+    /// Zooms by the change in distance between two fingers around where
+    /// they were, then pans along with them.
+    fn on_pinch(&mut self, before: (Position<i32>, Position<i32>), after: (Position<i32>, Position<i32>)) -> JsError {
+        fn distance((a, b): (Position<i32>, Position<i32>)) -> f64 {
+            ((a.x() - b.x()) as f64).hypot((a.y() - b.y()) as f64)
+        }
 
-        if player.stone == Stone::White {
-            stone_color = "White";
+        fn midpoint((a, b): (Position<i32>, Position<i32>)) -> (f64, f64) {
+            ((a.x() + b.x()) as f64 / 2_f64, (a.y() + b.y()) as f64 / 2_f64)
         }
 
-        new_player.set_inner_html(&format!("
-            <th>{}</th>
-            <th>{}</th>
-        ", player.username, stone_color));
+        let board_size = self.board_size as f64;
+        let (before_x, before_y) = midpoint(before);
+        let (after_x, after_y) = midpoint(after);
 
-        new_player.set_id(&format!("player-{}", player.username));
+        if distance(before) > 0_f64 {
+            self.view.zoom_at(distance(after) / distance(before), before_x, before_y, board_size);
+        }
 
-        player_list.append_child(&new_player)?;
+        self.view.pan(after_x - before_x, after_y - before_y, board_size);
 
-        Ok(())
+        self.on_view_change()
     }
 
-    fn on_player_remove(username: String) -> JsError {
-        let document = web_sys::window()
-            .unwrap()
-            .document()
-            .unwrap();
-
-        let player = document.get_element_by_id(&format!("player-{}", username))
-            .unwrap();
-        player.remove();
+    fn on_zoom(&mut self, factor: f64, x: i32, y: i32) -> JsError {
+        self.view.zoom_at(factor, x as f64, y as f64, self.board_size as f64);
 
-        Ok(())
+        self.on_view_change()
     }
 
-    fn on_player_already_logged_in() -> JsError {
-        Ok(())
+    fn on_reset_view(&mut self) -> JsError {
+        self.view = View::default();
+
+        self.on_view_change()
     }
 
-    fn login_form_reset() -> JsError {
+    /// Offers the reset button only while the board is zoomed in.
+    fn on_view_change(&mut self) -> JsError {
         let document = web_sys::window()
             .unwrap()
             .document()
             .unwrap();
 
-        let login_username = document.get_element_by_id("loginUsername")
-            .unwrap();
-        let login_room = document.get_element_by_id("loginRoom")
-            .unwrap();
+        let reset_view_button = document.get_element_by_id("playResetView")
+            .unwrap()
+            .dyn_into::<HtmlElement>()?;
 
-        login_username.set_class_name("form-control");
-        login_room.set_class_name("form-control");
+        reset_view_button.set_hidden(self.view.is_identity());
 
-        if let Some(login_username_error) = document.get_element_by_id("loginUsernameError") {
-            login_username_error.remove();
-        }
+        self.request_render()
+    }
 
-        if let Some(login_room_error) = document.get_element_by_id("loginRoomError") {
-            login_room_error.remove();
+    fn on_confirm_place(&mut self) -> JsError {
+        if let Some(position) = self.staged.take() {
+            self.place(&position)?;
         }
 
+        self.set_staged(None)?;
+
         Ok(())
     }
 
-    fn status_bar_size() -> JsError {
+    /// Stages a stone awaiting confirmation and shows the confirm button
+    /// while there is one.
+    fn set_staged(&mut self, staged: Option<Position<u32>>) -> JsError {
         let document = web_sys::window()
             .unwrap()
             .document()
             .unwrap();
 
-        let board_status = document.get_element_by_id("gameStatus")
+        let confirm_button = document.get_element_by_id("playConfirm")
             .unwrap()
             .dyn_into::<HtmlElement>()?;
 
-        let game_status_room = document.get_element_by_id("gameStatusRoom")
-            .unwrap()
-            .dyn_into::<HtmlElement>()?;
+        confirm_button.set_hidden(staged.is_none());
 
-        let game_status_chat = document.get_element_by_id("gameStatusChat")
-            .unwrap()
-            .dyn_into::<HtmlElement>()?;
+        self.staged = staged;
+        self.request_render()?;
 
-        let board_size = *BOARD_SIZE.lock()
-            .unwrap()
-            .get_mut();
-        let status_height = (board_size as f64 * 9_f64 / 10_f64) as u32;
-        let status_width = (board_size as f64 / 3_f64) as u32;
+        Ok(())
+    }
 
-        board_status.style()
-            .set_property("width", &format!("{}px", status_width))?;
-        let partial_height = (status_height as f64 * 9_f64 / 20_f64) as u32;
-        game_status_room.style()
-            .set_property("height", &format!("{}px", partial_height))?;
-        game_status_chat.style()
-            .set_property("height", &format!("{}px", partial_height))?;
+    fn side_length(&self) -> Option<usize> {
+        self.state.room
+            .as_ref()
+            .map(|room| room.board.stones.len())
+    }
 
-        let pass_button = document.get_element_by_id("playPass")
-            .unwrap()
-            .dyn_into::<HtmlElement>()?;
-        let quit_button = document.get_element_by_id("playQuit")
-            .unwrap()
-            .dyn_into::<HtmlElement>()?;
+    fn set_cursor(&mut self, cursor: Option<(usize, usize)>) -> JsError {
+        if cursor != self.cursor {
+            self.cursor = cursor;
+            self.request_render()?;
+        }
 
-        let chat_box = document.get_element_by_id("gameStatusChatMessages")
-            .unwrap()
-            .dyn_into::<HtmlElement>()?;
+        Ok(())
+    }
 
-        let chat_height = (partial_height as f64 * (10_f64 / 15_f64)) as u32;
+    /// Moves the keyboard cursor in screen directions, whichever way the
+    /// board is turned, starting from the centre the first time it is used.
+    fn on_cursor_move(&mut self, dx: i32, dy: i32) -> JsError {
+        let side_length = match self.side_length() {
+            Some(side_length) if side_length > 0 => side_length,
+            _ => return Ok(()),
+        };
 
-        let game_status_chat_input = document.get_element_by_id("gameStatusChatInput")
-            .unwrap()
-            .dyn_into::<HtmlElement>()?;
+        self.typed.clear();
 
-        game_status_chat_input.style()
-            .set_property("margin-left", &format!("8%"))?;
+        let cursor = match self.cursor {
+            Some((x, y)) => {
+                let (x, y) = self.orientation.to_view(x, y, side_length);
+                let last = side_length as i32 - 1;
 
-        pass_button.style()
-            .set_property("margin-top", &format!("8vh"))?;
-        quit_button.style()
-            .set_property("margin-top", &format!("8vh"))?;
-        
-        chat_box.style()
-            .set_property("height", &format!("{}px", chat_height))?;
+                self.orientation.to_board((x as i32 + dx).max(0).min(last) as usize,
+                                          (y as i32 + dy).max(0).min(last) as usize,
+                                          side_length)
+            }
+            None => (side_length / 2, side_length / 2),
+        };
+
+        self.set_cursor(Some(cursor))?;
 
         Ok(())
     }
 
-    fn status_bar_header(room_name: String) -> JsError {
-        let document = web_sys::window()
-            .unwrap()
-            .document()
-            .unwrap();
-
-        let players = STATE.lock()
-            .unwrap()
-            .borrow()
-            .room
-            .as_ref()
-            .unwrap()
-            .players
-            .clone();
-
-        let status_bar_title = document.get_element_by_id("gameStatusRoomTitle")
-            .unwrap()
-            .dyn_into::<HtmlElement>()?;
-
-        status_bar_title.set_inner_text(&format!("Room {}", room_name));
+    fn on_cursor_place(&mut self) -> JsError {
+        self.typed.clear();
 
-        let player_list = document.get_element_by_id("gameStatusRoomPlayers")
-            .unwrap();
-        
-        for (username, val) in players.iter() {
-            let player = document.create_element("tr")?;
+        if let Some((x, y)) = self.cursor {
+            self.choose(Position(x as u32, y as u32), self.confirm_moves)?;
+        }
 
-            let mut stone_color = "Black";
+        Ok(())
+    }
 
-            if val.stone == Stone::White {
-                stone_color = "White";
-            }
+    /// Collects typed coordinates, jumping the cursor as soon as the letters
+    /// and digits so far name an intersection.
+    fn on_cursor_type(&mut self, c: char) -> JsError {
+        let side_length = match self.side_length() {
+            Some(side_length) => side_length,
+            None => return Ok(()),
+        };
+
+        let c = c.to_ascii_uppercase();
+
+        let ends_in_digit = self.typed
+            .chars()
+            .last()
+            .is_some_and(|last| last.is_ascii_digit());
+
+        // A letter after digits starts a new coordinate, and a coordinate
+        // never starts with a digit.
+        if (c.is_ascii_alphabetic() && ends_in_digit) || (c.is_ascii_digit() && self.typed.is_empty()) {
+            self.typed.clear();
+        }
 
-            player.set_inner_html(&format!("
-                <th>{}</th>
-                <th>{}</th>
-            ", username, stone_color));
+        if c.is_ascii_digit() && self.typed.is_empty() {
+            return Ok(());
+        }
 
-            player.set_id(&format!("player-{}", username));
+        self.typed.push(c);
 
-            player_list.append_child(&player)?;
+        if let Some(cursor) = parse_coordinate(&self.typed, side_length) {
+            self.set_cursor(Some(cursor))?;
         }
 
         Ok(())
     }
 
-    fn get_piece_position(x_i: i32, y_i: i32, inner_begin: f64, inner_size: f64, line_space: f64) -> Option<Position<u32>> {
-        if x_i < (inner_begin - 4_f64 * inner_begin / 9_f64) as i32
-        || x_i > (inner_begin + inner_size + 1_f64 * inner_begin / 18_f64) as i32
-        || y_i < (inner_begin - 4_f64 * inner_begin / 9_f64) as i32
-        || y_i > (inner_begin + inner_size + 1_f64 * inner_begin / 18_f64) as i32
-        {
-            return None;
-        } else {
-            let x = x_i as f64 - inner_begin + line_space / 2_f64;
-            let y = y_i as f64 - inner_begin + line_space / 2_f64;
-            let p_x = x / line_space;
-            let p_y = y / line_space;
+    fn on_mouse_out(&mut self) -> JsError {
+        self.preview = None;
+        self.drag = None;
 
-            return Some(Position(p_x as u32, p_y as u32));
-        }
+        Ok(())
     }
 
-    fn on_mouse_click(x: i32, y: i32) -> JsError {
-        let mut playing = true;
+    fn on_mouse_down(&mut self, x: i32, y: i32) -> JsError {
+        self.drag = Some(Drag {
+            start: Position(x, y),
+            last: Position(x, y),
+            panning: false,
+        });
 
-        if let None = STATE.lock()
-            .unwrap()
-            .borrow()
-            .line_space
-            .as_ref()
-        {
-            playing = false;
-        }
+        Ok(())
+    }
 
-        if playing {
-            let line_space = STATE.lock()
-                .unwrap()
-                .borrow()
-                .line_space
-                .unwrap();
+    fn on_mouse_move(&mut self, x: i32, y: i32) -> JsError {
+        self.preview = Some(Position(x, y));
 
-            let inner_size = STATE.lock()
-                .unwrap()
-                .borrow()
-                .inner_size
-                .unwrap();
+        let delta = match self.drag.as_mut() {
+            Some(drag) => {
+                if ! drag.panning
+                && ((x - drag.start.x()).abs() > 4 || (y - drag.start.y()).abs() > 4)
+                {
+                    drag.panning = true;
+                }
 
-            let inner_begin = STATE.lock()
-                .unwrap()
-                .borrow()
-                .inner_begin
-                .unwrap();
+                let delta = (x - drag.last.x(), y - drag.last.y());
+                drag.last = Position(x, y);
 
-            if let Some(position) = Game::get_piece_position(x, y, inner_begin, inner_size, line_space) {
-                let message = ClientMessage::Place(Some(Position(position.x() as u8, position.y() as u8)));
-                Game::ws_send_message(&message)?;
+                Some(delta).filter(|_| drag.panning)
             }
+            None => None,
+        };
+
+        if let Some((dx, dy)) = delta {
+            self.view.pan(dx as f64, dy as f64, self.board_size as f64);
+            self.on_view_change()?;
         }
 
         Ok(())
     }
 
-    fn on_next_turn(username: String) -> JsError {
+    fn set_quit_button(dispatcher: &Dispatcher) -> JsError {
         let document = web_sys::window()
             .unwrap()
             .document()
             .unwrap();
 
-        STATE.lock()
-            .unwrap()
-            .borrow_mut()
-            .room
-            .as_mut()
-            .unwrap()
-            .current_player = username.clone();
-
-        let player_table = document.get_element_by_id("gameStatusRoomPlayers")
+        let quit_button = document.get_element_by_id("playQuit")
             .unwrap()
             .dyn_into::<HtmlElement>()?;
 
-        let players = player_table.children();
+        let on_quit = dispatcher.callback(Event::Quit);
 
-        for player_i in 0..players.length() {
-            let player = players.item(player_i)
-                .unwrap()
-                .dyn_into::<HtmlElement>()?;
-
-            player.style()
-                .set_property("background-color", "#ffffff")?;
-        }
+        quit_button.set_onclick(Some(on_quit.as_ref().unchecked_ref()));
 
-        let player = document.get_element_by_id(&format!("player-{}", username))
-            .unwrap()
-            .dyn_into::<HtmlElement>()?;
+        on_quit.forget();
+        
+        Ok(())
+    }
 
-        player.style()
-            .set_property("background-color", "grey")?;
+    fn on_quit(&mut self) -> JsError {
+        if let Some(ws) = self.ws.as_ref() {
+            ws.close()?;
+        }
 
         Ok(())
     }
 
-    fn on_chat_message_received(message: String) -> JsError {
+    fn set_pass_button(dispatcher: &Dispatcher) -> JsError {
         let document = web_sys::window()
             .unwrap()
             .document()
             .unwrap();
 
-        let chat_area = document.get_element_by_id("gameStatusChatMessages")
+        let pass_button = document.get_element_by_id("playPass")
             .unwrap()
             .dyn_into::<HtmlElement>()?;
 
-        let message = message.replace("<", "&lt;");
-        let message = message.replace(">", "&gt;");
+        let on_pass = dispatcher.callback(Event::Pass);
 
-        let new_message = document.create_element("div")?
-            .dyn_into::<HtmlElement>()?;
+        pass_button.set_onclick(Some(on_pass.as_ref().unchecked_ref()));
 
-        new_message.set_inner_html(&(message + "<br>"));
+        on_pass.forget();
 
-        chat_area.append_child(&new_message)?;
+        Ok(())
+    }
 
-        chat_area.set_scroll_top(chat_area.scroll_height());
+    fn on_pass(&mut self) -> JsError {
+        if self.state.spectating {
+            return Ok(());
+        }
+
+        let message = ClientMessage::Place(None);
+        self.ws_send_message(&message)?;
 
         Ok(())
     }
 
-    fn on_mouse_out() -> JsError {
-        PREVIEW.lock()
-            .unwrap()
-            .borrow_mut()
-            .0 = 0;
-        PREVIEW.lock()
-            .unwrap()
-            .borrow_mut()
-            .1 = 0;
+    fn on_resign(&mut self) -> JsError {
+        if self.state.spectating {
+            return Ok(());
+        }
+
+        self.ws_send_message(&ClientMessage::Resign)?;
 
         Ok(())
     }
 
-    fn on_mouse_move(x: i32, y: i32) -> JsError {
-        PREVIEW.lock()
+    fn set_message_button(dispatcher: &Dispatcher) -> JsError {
+        let document = web_sys::window()
             .unwrap()
-            .borrow_mut()
-            .0 = x;
-        PREVIEW.lock()
+            .document()
+            .unwrap();
+
+        let message_button = document.get_element_by_id("gameStatusChatSubmit")
             .unwrap()
-            .borrow_mut()
-            .1 = y;
+            .dyn_into::<HtmlElement>()?;
+
+        let on_message = dispatcher.callback(Event::ChatSubmit);
+
+        message_button.set_onclick(Some(on_message.as_ref().unchecked_ref()));
+
+        on_message.forget();
 
         Ok(())
     }
 
-    fn set_quit_button() -> JsError {
+    fn on_chat_submit(&mut self) -> JsError {
         let document = web_sys::window()
             .unwrap()
             .document()
             .unwrap();
 
-        let quit_button = document.get_element_by_id("playQuit")
+        let input_box = document.get_element_by_id("gameStatusChatInput")
             .unwrap()
-            .dyn_into::<HtmlElement>()?;
+            .dyn_into::<HtmlInputElement>()?;
 
-        let on_quit = Closure::wrap(Box::new(|| {
-            STATE.lock()
-                .unwrap()
-                .borrow()
-                .ws
-                .as_ref()
-                .unwrap()
-                .close()?;
+        let message = input_box.value()
+            .as_str()
+            .to_string();
 
-            Ok::<(), JsValue>(())
-        }) as Box<dyn FnMut() -> JsError>);
+        if message.trim().is_empty() {
+            return Ok(());
+        }
 
-        quit_button.set_onclick(Some(on_quit.as_ref().unchecked_ref()));
+        input_box.set_value("");
+
+        match chat::parse(&message) {
+            Ok(Command::Say(text)) =>
+                self.chat_send(ClientMessage::Chat, text)?,
+            Ok(Command::Me(text)) =>
+                self.chat_send(ClientMessage::Emote, text)?,
+            Ok(Command::Pass) =>
+                self.on_pass()?,
+            Ok(Command::Resign) =>
+                self.on_resign()?,
+            Ok(Command::Who) =>
+                self.chat_who()?,
+            Ok(Command::Clear) => {
+                document.get_element_by_id("gameStatusChatMessages")
+                    .unwrap()
+                    .set_inner_html("");
+            }
+            Ok(Command::Help) => {
+                for (command, description) in chat::COMMANDS.iter() {
+                    self.chat_notice(&format!("{} \u{2014} {}", command, self.locale.text(*description)))?;
+                }
+            }
+            Err(CommandError::MissingText(command)) =>
+                self.chat_notice(&self.locale.text(Message::CommandNeedsText(&command)))?,
+            Err(CommandError::Unknown(command)) =>
+                self.chat_notice(&self.locale.text(Message::UnknownCommand(&command)))?,
+        }
 
-        on_quit.forget();
-        
         Ok(())
     }
 
-    fn set_pass_button() -> JsError {
+    /// Points out intersections named in chat. One set of listeners on the
+    /// chat pane serves every coordinate button in it: hovering or focusing
+    /// one marks the intersection while it lasts, clicking marks it for a
+    /// couple of seconds.
+    fn set_chat_coordinates(dispatcher: &Dispatcher) -> JsError {
         let document = web_sys::window()
             .unwrap()
             .document()
             .unwrap();
 
-        let pass_button = document.get_element_by_id("playPass")
-            .unwrap()
-            .dyn_into::<HtmlElement>()?;
+        let chat_area = document.get_element_by_id("gameStatusChatMessages")
+            .unwrap();
+
+        fn coordinate(e: &web_sys::Event) -> Option<(usize, usize)> {
+            let button = e.target()?
+                .dyn_into::<web_sys::Element>()
+                .ok()?
+                .closest(".chat-coordinate")
+                .ok()??;
+
+            Some((button.get_attribute("data-x")?.parse().ok()?,
+                  button.get_attribute("data-y")?.parse().ok()?))
+        }
 
-        let on_pass = Closure::wrap(Box::new(|| {
-            let message = ClientMessage::Place(None);
-            Game::ws_send_message(&message)?;
+        type Listener = (&'static str, fn((usize, usize)) -> Event);
+
+        let listeners: [Listener; 5] = [
+            ("mouseover", |(x, y)| Event::MarkerShow(x, y, false)),
+            ("focusin", |(x, y)| Event::MarkerShow(x, y, false)),
+            ("click", |(x, y)| Event::MarkerShow(x, y, true)),
+            ("mouseout", |_| Event::MarkerHide),
+            ("focusout", |_| Event::MarkerHide),
+        ];
+
+        for (kind, event) in listeners.iter() {
+            let event = *event;
+            let listener_dispatcher = dispatcher.clone();
+            let listener = Closure::wrap(Box::new(move |e: web_sys::Event| {
+                if let Some(position) = coordinate(&e) {
+                    listener_dispatcher.dispatch(event(position));
+                }
 
-            Ok::<(), JsValue>(())
-        }) as Box<dyn FnMut() -> JsError>);
+                Ok::<(), JsValue>(())
+            }) as Box<dyn FnMut(web_sys::Event) -> JsError>);
 
-        pass_button.set_onclick(Some(on_pass.as_ref().unchecked_ref()));
+            chat_area.add_event_listener_with_callback(kind, listener.as_ref().unchecked_ref())?;
 
-        on_pass.forget();
+            listener.forget();
+        }
 
         Ok(())
     }
 
-    fn set_message_button() -> JsError {
-        let document = web_sys::window()
-            .unwrap()
-            .document()
+    fn on_marker_show(&mut self, x: usize, y: usize, timed: bool) -> JsError {
+        let window = web_sys::window()
             .unwrap();
 
-        let message_button = document.get_element_by_id("gameStatusChatSubmit")
-            .unwrap()
-            .dyn_into::<HtmlElement>()?;
-
-        let on_message = Closure::wrap(Box::new(move || {
-            let input_box = document.get_element_by_id("gameStatusChatInput")
-                .unwrap()
-                .dyn_into::<HtmlInputElement>()?;
+        if let Some(marker_timeout) = self.marker_timeout.take() {
+            window.clear_timeout_with_handle(marker_timeout);
+        }
 
-            let message = input_box.value()
-                .as_str()
-                .to_string();
+        if timed {
+            let dispatcher = self.dispatcher();
+            let on_timeout = Closure::once_into_js(move || {
+                dispatcher.dispatch(Event::MarkerTimeout);
+            });
 
-            if message.len() > 0 {
-                let username = STATE.lock()
-                    .unwrap()
-                    .borrow()
-                    .room
-                    .as_ref()
-                    .unwrap()
-                    .self_player
-                    .clone();
+            self.marker_timeout = Some(window.set_timeout_with_callback_and_timeout_and_arguments_0(
+                on_timeout.unchecked_ref(), 2000)?);
+        }
 
-                let client_message = ClientMessage::Chat("<".to_owned() + &username + ">" + ": " + &message);
-                Game::ws_send_message(&client_message)?;
+        self.set_marker(Some((x, y)))
+    }
 
-                input_box.set_value("");
-            }
+    fn set_marker(&mut self, marker: Option<(usize, usize)>) -> JsError {
+        if marker != self.marker {
+            self.marker = marker;
+            self.request_render()?;
+        }
 
-            Ok::<(), JsValue>(())
-        }) as Box<dyn FnMut() -> JsError>);
+        Ok(())
+    }
 
-        message_button.set_onclick(Some(on_message.as_ref().unchecked_ref()));
+    /// Sends `text` to the room as `kind`, i.e. `ClientMessage::Chat` or
+    /// `ClientMessage::Emote`.
+    fn chat_send(&mut self, kind: fn(ChatChannel, String) -> ClientMessage, text: String) -> JsError {
+        // The server fills in who sent it and when. Spectators talk among
+        // themselves, so they cannot give the players hints.
+        let channel = if self.state.spectating {
+            ChatChannel::Spectators
+        } else {
+            ChatChannel::Room
+        };
 
-        on_message.forget();
+        let client_message = kind(channel, text);
+        self.ws_send_message(&client_message)?;
 
         Ok(())
     }
 
-    fn set_enter_key() -> JsError {
+    /// Shows a line in the chat pane that only this client sees.
+    fn chat_notice(&mut self, text: &str) -> JsError {
+        self.on_chat_message_received(ChatMessage {
+            sender: None,
+            timestamp: js_sys::Date::now() as u64,
+            channel: ChatChannel::System,
+            body: text.to_string(),
+            emote: false,
+        })
+    }
+
+    fn chat_who(&mut self) -> JsError {
+        let room = match self.state.room.as_ref() {
+            Some(room) => room,
+            None => return Ok(()),
+        };
+
+        let players = room.players
+            .iter()
+            .map(|(username, player)| self.locale.text(Message::Player {
+                username,
+                stone: player.stone,
+                to_play: *username == room.current_player,
+            }))
+            .collect::<Vec<String>>();
+
+        let mut text = self.locale.text(Message::WhoPlayers(&players.join(", ")));
+
+        if ! room.spectators.is_empty() {
+            text += " ";
+            text += &self.locale.text(Message::WhoSpectators(&room.spectators.join(", ")));
+        }
+
+        self.chat_notice(&text)
+    }
+
+    fn set_enter_key(dispatcher: &Dispatcher) -> JsError {
         let document = web_sys::window()
             .unwrap()
             .document()
             .unwrap();
 
-        let submit_key = document.get_element_by_id("loginSubmit")
-            .unwrap()
-            .dyn_into::<HtmlElement>()?;
         let login = document.get_element_by_id("loginUsername")
             .unwrap()
             .dyn_into::<HtmlInputElement>()?;
@@ -1450,25 +3406,23 @@ impl Game {
             .unwrap()
             .dyn_into::<HtmlInputElement>()?;
 
+        let login_dispatcher = dispatcher.clone();
         let login_key_handler = Closure::wrap(Box::new(move |e: KeyboardEvent| {
             if e.key_code() == 13 {
-                submit_key.click();
+                login_dispatcher.dispatch(Event::LoginSubmit);
             }
 
             Ok::<(), JsValue>(())
         }) as Box<dyn FnMut(KeyboardEvent) -> JsError>);
 
-        let chat_submit = document.get_element_by_id("gameStatusChatSubmit")
-            .unwrap()
-            .dyn_into::<HtmlElement>()?;
-
         let chat = document.get_element_by_id("gameStatusChatInput")
             .unwrap()
             .dyn_into::<HtmlInputElement>()?;
 
+        let chat_dispatcher = dispatcher.clone();
         let chat_key_handler = Closure::wrap(Box::new(move |e: KeyboardEvent| {
             if e.key_code() == 13 {
-                chat_submit.click();
+                chat_dispatcher.dispatch(Event::ChatSubmit);
             }
 
             Ok::<(), JsValue>(())
@@ -1484,91 +3438,423 @@ impl Game {
         Ok(())
     }
 
-    fn render() -> JsError {
+    /// Marks the board as needing a redraw. Drawing happens at most once per
+    /// animation frame, and not at all while the tab is hidden.
+    fn request_render(&mut self) -> JsError {
+        self.render_dirty = true;
+
+        self.schedule_render()?;
+
+        Ok(())
+    }
+
+    fn schedule_render(&mut self) -> JsError {
+        let window = web_sys::window()
+            .unwrap();
+
+        if window.document()
+            .unwrap()
+            .hidden()
+        {
+            return Ok(());
+        }
+
+        if ! self.render_dirty || self.render_frame.is_some() {
+            return Ok(());
+        }
+
+        let dispatcher = self.dispatcher();
+        let on_frame = Closure::once_into_js(move || {
+            dispatcher.dispatch(Event::AnimationFrame);
+        });
+
+        let render_frame = window.request_animation_frame(on_frame.unchecked_ref())?;
+
+        self.render_frame = Some(render_frame);
+
+        Ok(())
+    }
+
+    fn on_animation_frame(&mut self) -> JsError {
+        self.render_frame = None;
+        self.render_dirty = false;
+
+        self.render()?;
+
+        Ok(())
+    }
+
+    fn set_visibility_change(dispatcher: &Dispatcher) -> JsError {
         let document = web_sys::window()
             .unwrap()
             .document()
             .unwrap();
 
-        if let None = STATE.lock()
+        let on_visibility_change = dispatcher.callback(Event::VisibilityChange);
+
+        document.add_event_listener_with_callback("visibilitychange", on_visibility_change.as_ref().unchecked_ref())?;
+
+        on_visibility_change.forget();
+
+        Ok(())
+    }
+
+    fn on_visibility_change(&mut self) -> JsError {
+        let window = web_sys::window()
+            .unwrap();
+
+        if window.document()
             .unwrap()
-            .borrow()
-            .room
-            .as_ref()
+            .hidden()
         {
-            return Err(JsValue::from_str("Game not initialized yet."));
+            if let Some(render_frame) = self.render_frame.take() {
+                window.cancel_animation_frame(render_frame)?;
+            }
+        } else {
+            self.schedule_render()?;
         }
 
-        let board_size = *BOARD_SIZE.lock()
-            .unwrap()
-            .get_mut() as f64;
+        Ok(())
+    }
 
-        let side_length = STATE.lock()
-            .unwrap()
-            .borrow()
-            .room
-            .as_ref()
-            .unwrap()
-            .board
-            .stones
-            .len() as f64;
-        
-        let game_board = document.get_element_by_id("gameBoard")
+    fn render(&mut self) -> JsError {
+        let document = web_sys::window()
             .unwrap()
-            .dyn_into::<HtmlCanvasElement>()?;
+            .document()
+            .unwrap();
+
+        let orientation = self.orientation;
+
+        // Everything below works in view coordinates, as the board is drawn.
+        // Without a room there is no board, e.g. for a frame that was still
+        // scheduled when the connection closed.
+        let stones = match self.state.room.as_ref() {
+            Some(room) => orientation.view_grid(&room.board.stones),
+            None => return Ok(()),
+        };
+
+        let hover_stone = self.state.self_stone();
+
+        let board_size_px = self.board_size;
+        let board_size = board_size_px as f64;
+
+        let side_length = stones.len();
 
         let dpr = web_sys::window()
             .unwrap()
             .device_pixel_ratio()
             .max(1_f64);
 
-        game_board.style()
-            .set_property("width", &format!("{}px", board_size))?;
-        game_board.style()
-            .set_property("height", &format!("{}px", board_size))?;
+        // Layers are drawn at the zoomed resolution so that zooming in stays
+        // sharp, within what a canvas can reasonably hold. The resolution
+        // goes up in powers of two, so most zoom steps only composite the
+        // layers at another scale instead of redrawing them.
+        let view = self.view;
+        let layer_scale = (dpr * view.zoom()).log2()
+            .ceil()
+            .exp2()
+            .min(MAX_LAYER_PIXELS / board_size);
+
+        let geometry = Geometry::new(board_size, side_length);
+        let inner_size = geometry.inner_size;
+        let inner_begin = geometry.inner_begin;
+        let line_space = geometry.line_space;
+
+        self.geometry = Some(geometry);
+
+        let game_board = document.get_element_by_id("gameBoard")
+            .unwrap()
+            .dyn_into::<HtmlCanvasElement>()?;
+
+        let mut layers = match self.layers.take() {
+            Some(layers) => layers,
+            None => Layers {
+                board: document.create_element("canvas")?
+                    .dyn_into::<HtmlCanvasElement>()?,
+                stones: document.create_element("canvas")?
+                    .dyn_into::<HtmlCanvasElement>()?,
+                board_key: None,
+                stones_drawn: Vec::new(),
+                hover: None,
+                hover_staged: false,
+                cursor: None,
+                marker: None,
+                view: View::default(),
+            },
+        };
+
+        // Cells whose pixels changed since the last frame. An empty list with
+        // dirty_all unset means nothing needs to reach the visible canvas.
+        let mut dirty: Vec<(usize, usize)> = Vec::new();
+        let mut dirty_all = false;
+
+        let board_key = (board_size_px, side_length, layer_scale, orientation);
+
+        if layers.board_key != Some(board_key) {
+            let canvas_size = (board_size * dpr).ceil() as u32;
+            let layer_size = (board_size * layer_scale).ceil() as u32;
+
+            game_board.style()
+                .set_property("width", &format!("{}px", board_size))?;
+            game_board.style()
+                .set_property("height", &format!("{}px", board_size))?;
+
+            game_board.set_width(canvas_size);
+            game_board.set_height(canvas_size);
+
+            for canvas in &[&layers.board, &layers.stones] {
+                canvas.set_width(layer_size);
+                canvas.set_height(layer_size);
+            }
+
+            let board_ctx = App::layer_context(&layers.board, layer_scale)?;
+            let complete = App::render_board_layer(&board_ctx, orientation, board_size, side_length as f64, inner_begin, inner_size, line_space)?;
+
+            // A board drawn before the wood loaded is drawn again once it has.
+            layers.board_key = if complete { Some(board_key) } else { None };
+            layers.stones_drawn = Vec::new();
+            dirty_all = true;
+        }
+
+        let stones_ctx = App::layer_context(&layers.stones, layer_scale)?;
+
+        if layers.stones_drawn.len() != side_length {
+            stones_ctx.clear_rect(0_f64, 0_f64, board_size, board_size);
+
+            for (y, row) in stones.iter().enumerate() {
+                for (x, spot) in row.iter().enumerate() {
+                    if let Some(stone) = spot {
+                        App::render_stone(&stones_ctx, *stone, x, y, inner_begin, line_space, 1_f64)?;
+                    }
+                }
+            }
+
+            dirty_all = true;
+        } else {
+            for (y, row) in stones.iter().enumerate() {
+                for (x, spot) in row.iter().enumerate() {
+                    if *spot == layers.stones_drawn[y][x] {
+                        continue;
+                    }
+
+                    stones_ctx.clear_rect(inner_begin + (x as f64 - 0.5_f64) * line_space,
+                                          inner_begin + (y as f64 - 0.5_f64) * line_space,
+                                          line_space,
+                                          line_space);
+
+                    if let Some(stone) = spot {
+                        App::render_stone(&stones_ctx, *stone, x, y, inner_begin, line_space, 1_f64)?;
+                    }
+
+                    dirty.push((x, y));
+                }
+            }
+        }
+
+        if layers.view != view {
+            layers.view = view;
+            dirty_all = true;
+        }
+
+        let mut hover = None;
+        let mut hover_staged = false;
+
+        if hover_stone.is_some() {
+            // A finger on the board shows where it is, over any stone
+            // already staged.
+            let location = match self.staged {
+                Some(staged) if ! self.touching => Some(staged),
+                _ => self.preview.and_then(|preview| self.board_position(preview.x(), preview.y())),
+            };
 
-        game_board.set_attribute("width", &format!("{}", (board_size * dpr).ceil() as u32))?;
-        game_board.set_attribute("height", &format!("{}", (board_size * dpr).ceil() as u32))?;
+            if let Some(location) = location {
+                let (x, y) = orientation.to_view(location.x() as usize, location.y() as usize, side_length);
 
+                if stones[y][x].is_none() {
+                    hover = Some((x, y));
+                    hover_staged = self.staged == Some(location);
+                }
+            }
+        }
+
+        if hover != layers.hover || hover_staged != layers.hover_staged {
+            dirty.extend(layers.hover);
+            dirty.extend(hover);
+            layers.hover = hover;
+            layers.hover_staged = hover_staged;
+        }
+
+        let cursor = self.cursor
+            .filter(|(x, y)| *x < side_length && *y < side_length)
+            .map(|(x, y)| orientation.to_view(x, y, side_length));
+
+        if cursor != layers.cursor {
+            dirty.extend(layers.cursor);
+            dirty.extend(cursor);
+            layers.cursor = cursor;
+        }
+
+        let marker = self.marker
+            .filter(|(x, y)| *x < side_length && *y < side_length)
+            .map(|(x, y)| orientation.to_view(x, y, side_length));
+
+        if marker != layers.marker {
+            dirty.extend(layers.marker);
+            dirty.extend(marker);
+            layers.marker = marker;
+        }
+
+        layers.stones_drawn = stones;
 
         let ctx = game_board.get_context("2d")
             .unwrap()
             .unwrap()
             .dyn_into::<CanvasRenderingContext2d>()?;
 
-        ctx.scale(dpr, dpr)?;
+        ctx.set_transform(dpr, 0_f64, 0_f64, dpr, 0_f64, 0_f64)?;
 
-        let image = document.get_element_by_id("backgroundWood")
+        if dirty_all {
+            ctx.clear_rect(0_f64, 0_f64, board_size, board_size);
+        }
+
+        ctx.set_transform(dpr * view.zoom(), 0_f64, 0_f64, dpr * view.zoom(), dpr * view.offset_x, dpr * view.offset_y)?;
+
+        if dirty_all {
+            ctx.draw_image_with_html_canvas_element_and_dw_and_dh(&layers.board, 0_f64, 0_f64, board_size, board_size)?;
+            ctx.draw_image_with_html_canvas_element_and_dw_and_dh(&layers.stones, 0_f64, 0_f64, board_size, board_size)?;
+        } else {
+            for (x, y) in dirty.iter() {
+                // Whole pixels with a margin, so that antialiased edges are
+                // copied back instead of leaving seams around the cell.
+                let left = (inner_begin + (*x as f64 - 0.5_f64) * line_space).floor() - 1_f64;
+                let top = (inner_begin + (*y as f64 - 0.5_f64) * line_space).floor() - 1_f64;
+                let size = line_space.ceil() + 2_f64;
+
+                ctx.clear_rect(left, top, size, size);
+
+                for layer in &[&layers.board, &layers.stones] {
+                    ctx.draw_image_with_html_canvas_element_and_sw_and_sh_and_dx_and_dy_and_dw_and_dh(
+                        layer,
+                        left * layer_scale, top * layer_scale, size * layer_scale, size * layer_scale,
+                        left, top, size, size)?;
+                }
+            }
+        }
+
+        if let (Some((x, y)), Some(stone)) = (hover, hover_stone) {
+            if dirty_all || dirty.contains(&(x, y)) {
+                App::render_stone(&ctx, stone, x, y, inner_begin, line_space, 0.5_f64)?;
+
+                if hover_staged {
+                    App::render_staged(&ctx, x, y, inner_begin, line_space)?;
+                }
+            }
+        }
+
+        if let Some((x, y)) = cursor {
+            if dirty_all || dirty.contains(&(x, y)) {
+                App::render_cursor(&ctx, x, y, inner_begin, line_space)?;
+            }
+        }
+
+        if let Some((x, y)) = marker {
+            if dirty_all || dirty.contains(&(x, y)) {
+                App::render_marker(&ctx, x, y, inner_begin, line_space)?;
+            }
+        }
+
+        self.layers = Some(layers);
+
+        let game = document.get_element_by_id("game")
             .unwrap()
-            .dyn_into::<HtmlImageElement>()?;
-        ctx.draw_image_with_html_image_element_and_dw_and_dh(&image, 0_f64, 0_f64, board_size, board_size)?;
+            .dyn_into::<HtmlElement>()?;
+
+        game.set_hidden(false);
+
+        Ok(())
+    }
+
+    /// Outlines the cell around an intersection, kept inside the cell so a
+    /// dirty-cell redraw erases it.
+    fn render_cursor(ctx: &CanvasRenderingContext2d, x: usize, y: usize, inner_begin: f64, line_space: f64) -> JsError {
+        let inset = line_space * 0.1_f64;
+
+        ctx.set_stroke_style_str("#1e90ff");
+        ctx.set_line_width(2_f64);
+        ctx.stroke_rect(inner_begin + (x as f64 - 0.5_f64) * line_space + inset,
+                        inner_begin + (y as f64 - 0.5_f64) * line_space + inset,
+                        line_space - 2_f64 * inset,
+                        line_space - 2_f64 * inset);
+
+        Ok(())
+    }
+
+    /// Rings a staged stone, so it reads apart from the hover ghost while
+    /// it waits for the confirm button.
+    fn render_staged(ctx: &CanvasRenderingContext2d, x: usize, y: usize, inner_begin: f64, line_space: f64) -> JsError {
+        ctx.set_stroke_style_str("#1e90ff");
+        ctx.set_line_width(2_f64);
+        ctx.begin_path();
+        ctx.arc(inner_begin + x as f64 * line_space,
+                inner_begin + y as f64 * line_space,
+                line_space * (4_f64 / 9_f64),
+                0_f64,
+                2_f64 * std::f64::consts::PI)?;
+        ctx.stroke();
+
+        Ok(())
+    }
 
-        let inner_size = 0.74_f64 * board_size;
-        let inner_begin = (board_size - inner_size) / 2_f64;
+    /// Rings an intersection pointed out from chat, within its cell like
+    /// the cursor.
+    fn render_marker(ctx: &CanvasRenderingContext2d, x: usize, y: usize, inner_begin: f64, line_space: f64) -> JsError {
+        ctx.set_stroke_style_str("#ff8c00");
+        ctx.set_line_width(3_f64);
+        ctx.begin_path();
+        ctx.arc(inner_begin + x as f64 * line_space,
+                inner_begin + y as f64 * line_space,
+                line_space * 0.38_f64,
+                0_f64,
+                2_f64 * std::f64::consts::PI)?;
+        ctx.stroke();
+
+        Ok(())
+    }
 
-        STATE.lock()
+    fn layer_context(canvas: &HtmlCanvasElement, scale: f64) -> JsResult<CanvasRenderingContext2d> {
+        let ctx = canvas.get_context("2d")
             .unwrap()
-            .borrow_mut()
-            .inner_size = Some(inner_size);
+            .unwrap()
+            .dyn_into::<CanvasRenderingContext2d>()?;
 
-        STATE.lock()
+        ctx.set_transform(scale, 0_f64, 0_f64, scale, 0_f64, 0_f64)?;
+
+        Ok(ctx)
+    }
+
+    /// Draws the wood, lines, star points and labels. Returns whether the
+    /// wood was ready to draw.
+    fn render_board_layer(ctx: &CanvasRenderingContext2d, orientation: Orientation, board_size: f64, side_length: f64, inner_begin: f64, inner_size: f64, line_space: f64) -> JsResult<bool> {
+        let document = web_sys::window()
             .unwrap()
-            .borrow_mut()
-            .inner_begin = Some(inner_begin);
+            .document()
+            .unwrap();
+
+        let image = document.get_element_by_id("backgroundWood")
+            .unwrap()
+            .dyn_into::<HtmlImageElement>()?;
+        let complete = image.complete();
+
+        if complete {
+            ctx.draw_image_with_html_image_element_and_dw_and_dh(&image, 0_f64, 0_f64, board_size, board_size)?;
+        }
 
-        ctx.set_fill_style(&JsValue::from_str(&"black"));
+        ctx.set_fill_style_str("black");
         ctx.fill_rect(inner_begin - 1_f64, inner_begin - 1_f64, 1_f64, inner_size + 2_f64);
         ctx.fill_rect(inner_begin + inner_size, inner_begin - 1_f64, 1_f64, inner_size + 2_f64);
         ctx.fill_rect(inner_begin, inner_begin - 1_f64, inner_size, 1_f64);
         ctx.fill_rect(inner_begin, inner_begin + inner_size, inner_size, 1_f64);
 
-        let line_space = inner_size / (side_length - 1_f64);
-
-        STATE.lock()
-            .unwrap()
-            .borrow_mut()
-            .line_space = Some(line_space);
-
         for i in 1..=(side_length - 2_f64) as usize {
             ctx.fill_rect(inner_begin + i as f64 * line_space - 1_f64, inner_begin, 1_f64, inner_size);
             ctx.fill_rect(inner_begin, inner_begin + i as f64 * line_space - 1_f64, inner_size, 1_f64);
@@ -1617,145 +3903,55 @@ impl Game {
 
         ctx.set_font(&format!("{}px sans serif", font_size));
 
-        for i in 0..side_length as usize {
-            if i+1 < 10 {
-                ctx.fill_text(&format!("{}", i as u32 + 1), inner_begin - inner_begin * (7_f64 / 9_f64), inner_begin + line_space * i as f64 + 8_f64 * font_size as f64 / 20_f64)?;
-                ctx.fill_text(&format!("{}", i as u32 + 1), inner_begin + inner_size + 5_f64 * inner_begin / 9_f64, inner_begin + line_space * i as f64 + 8_f64 * font_size as f64 / 20_f64)?;
-            } else {
-                ctx.fill_text(&format!("{}", i as u32 + 1), inner_begin - inner_begin * (7_f64 / 9_f64) - font_size as f64 / 2.9_f64, inner_begin + line_space * i as f64 + 8_f64 * font_size as f64 / 20_f64)?;
-                ctx.fill_text(&format!("{}", i as u32 + 1), inner_begin + inner_size + 5_f64 * inner_begin / 9_f64 - font_size as f64 / 3_f64, inner_begin + line_space * i as f64 + 8_f64 * font_size as f64 / 20_f64)?;
-            }
+        // Labels are centred on their line, since a turned board can put
+        // numbers along the top and letters down the sides.
+        ctx.set_text_align("center");
 
-            let nest = (i as f64 / 26_f64).ceil().max(1_f64);
+        let side_offset = font_size as f64 * 0.28_f64;
 
-            let mut indicator = String::new();
-            for _ in 0..nest as usize {
-                indicator += &ALPHABET[i % 26].to_string();
-            }
-            
-            if indicator == "I" || indicator == "J" {
-                ctx.fill_text(&indicator, inner_begin + line_space * i as f64 - 7_f64 * font_size as f64 / 20_f64 + font_size as f64 / 4.7_f64, 4_f64 * inner_begin / 9_f64)?;
-                ctx.fill_text(&indicator, inner_begin + line_space * i as f64 - 7_f64 * font_size as f64 / 20_f64 + font_size as f64 / 4.7_f64, inner_begin + inner_size + 7_f64 * inner_begin / 9_f64)?;
-            } else {
-                ctx.fill_text(&indicator, inner_begin + line_space * i as f64 - 7_f64 * font_size as f64 / 20_f64, 4_f64 * inner_begin / 9_f64)?;
-                ctx.fill_text(&indicator, inner_begin + line_space * i as f64 - 7_f64 * font_size as f64 / 20_f64, inner_begin + inner_size + 7_f64 * inner_begin / 9_f64)?;
-            }
-        }
+        for i in 0..side_length as usize {
+            let row_label = orientation.row_label(i, side_length as usize);
+            let row_y = inner_begin + line_space * i as f64 + 8_f64 * font_size as f64 / 20_f64;
 
-        let username = STATE.lock()
-            .unwrap()
-            .borrow()
-            .room
-            .as_ref()
-            .unwrap()
-            .self_player
-            .clone();
+            ctx.fill_text(&row_label, 2_f64 * inner_begin / 9_f64 + side_offset, row_y)?;
+            ctx.fill_text(&row_label, inner_begin + inner_size + 5_f64 * inner_begin / 9_f64 + side_offset, row_y)?;
 
-        let mut player: Option<Player> = None;
+            let column_label = orientation.column_label(i, side_length as usize);
+            let column_x = inner_begin + line_space * i as f64;
 
-        for other_player in STATE.lock()
-            .unwrap()
-            .borrow()
-            .room
-            .as_ref()
-            .unwrap()
-            .players
-            .iter()
-        {
-            if other_player.0 == username {
-                player = Some(other_player.1.clone());
-            }
+            ctx.fill_text(&column_label, column_x, 4_f64 * inner_begin / 9_f64)?;
+            ctx.fill_text(&column_label, column_x, inner_begin + inner_size + 7_f64 * inner_begin / 9_f64)?;
         }
 
-        let player = player.unwrap();
-
-        for (y, row) in STATE.lock()
-            .unwrap()
-            .borrow()
-            .room
-            .as_ref()
-            .unwrap()
-            .board
-            .stones
-            .iter()
-            .enumerate()
-        {
-            for (x, spot) in row.iter().enumerate() {
-                let mouse_position = PREVIEW.lock()
-                    .unwrap()
-                    .borrow()
-                    .clone();
-
-                if let Some(location) = Game::get_piece_position(mouse_position.x(), mouse_position.y(), inner_begin, inner_size, line_space)
-                {
-                    if player.stone == Stone::Black {
-                        ctx.set_fill_style(&JsValue::from_str(&"#000000"));
-                        ctx.set_global_alpha(0.006_f64);
-                    } else {
-                        ctx.set_fill_style(&JsValue::from_str(&"#ffffff"));
-                        ctx.set_global_alpha(0.012_f64);
-                    }
-                    ctx.begin_path();
-                    ctx.arc(inner_begin + location.x() as f64 * line_space,
-                            inner_begin + location.y() as f64 * line_space,
-                            line_space * (4_f64 / 9_f64),
-                            0_f64,
-                            2_f64 * std::f64::consts::PI)?;
-                    ctx.fill();
-                    ctx.set_global_alpha(1_f64);
-                }
-
-                if let Some(stone) = spot {
-                    if *stone == Stone::Black {
-                        ctx.set_fill_style(&JsValue::from_str(&"#000000"));
-                    } else {
-                        ctx.set_fill_style(&JsValue::from_str(&"#ffffff"));
-                    }
+        Ok(complete)
+    }
 
-                    ctx.begin_path();
-                    ctx.arc(inner_begin + x as f64 * line_space,
-                            inner_begin + y as f64 * line_space,
-                            line_space * (4_f64 / 9_f64),
-                            0_f64,
-                            2_f64 * std::f64::consts::PI)?;
-                    ctx.fill();
-                }
-            }
+    fn render_stone(ctx: &CanvasRenderingContext2d, stone: Stone, x: usize, y: usize, inner_begin: f64, line_space: f64, alpha: f64) -> JsError {
+        if stone == Stone::Black {
+            ctx.set_fill_style_str("#000000");
+        } else {
+            ctx.set_fill_style_str("#ffffff");
         }
 
-        let game = document.get_element_by_id("game")
-            .unwrap()
-            .dyn_into::<HtmlElement>()?;
-
-        game.set_hidden(false);
+        ctx.set_global_alpha(alpha);
+        ctx.begin_path();
+        ctx.arc(inner_begin + x as f64 * line_space,
+                inner_begin + y as f64 * line_space,
+                line_space * (4_f64 / 9_f64),
+                0_f64,
+                2_f64 * std::f64::consts::PI)?;
+        ctx.fill();
+        ctx.set_global_alpha(1_f64);
 
         Ok(())
     }
 }
 
-lazy_static::lazy_static! {
-    static ref STATE: Mutex<RefCell<Game>> = Mutex::new(RefCell::new(Game {
-        ws: None,
-        room: None,
-        inner_begin: None,
-        inner_size: None,
-        line_space: None,
-    }));
-
-    static ref PREVIEW: Mutex<RefCell<Position<i32>>> = Mutex::new(RefCell::new(Position(0, 0)));
-
-    static ref BOARD_SIZE: Mutex<RefCell<u32>> = Mutex::new(RefCell::new(
-            Game::get_window_size()));
-}
-
-const ALPHABET: [char; 26] = ['A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J', 'K', 'L', 'M',
-                              'N', 'O', 'P', 'Q', 'R', 'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z'];
-
 #[wasm_bindgen(start)]
 pub fn main() -> JsError {
     console_error_panic_hook::set_once();
 
-    Game::login()?;
+    App::start()?;
 
     Ok(())
 }
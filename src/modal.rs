@@ -0,0 +1,558 @@
+//! The status dialog. A `Modal` describes what it shows and which events
+//! its buttons raise; opening it builds the `#status` element, which goes
+//! away again with the `OpenModal` handle. Showing, hiding, the backdrop
+//! and keyboard focus are all handled here, styled by Bootstrap's CSS but
+//! without its scripts.
+
+use {
+    crate::{
+        dom,
+        Dispatcher,
+        Event,
+        JsClosure,
+        JsClosureNone,
+        JsError,
+        JsResult,
+    },
+    wasm_bindgen::{
+        prelude::*,
+        JsCast,
+    },
+    web_sys::{
+        HtmlElement,
+        HtmlInputElement,
+        KeyboardEvent,
+        MouseEvent,
+    },
+};
+
+/// Elements that take focus with Tab.
+const FOCUSABLE: &str = "a[href], button:not([disabled]), input:not([disabled]), select:not([disabled]), textarea:not([disabled]), [tabindex]:not([tabindex=\"-1\"])";
+
+/// What a key pressed while a modal is open does to it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum KeyAction {
+    Dismiss,
+    /// Moves focus to the focusable element at this index.
+    Focus(usize),
+    /// Keeps focus where it is.
+    Stay,
+}
+
+/// Handles `key` in a modal with `count` focusable elements, of which the
+/// one at `focused` has focus. Tab cycles through them without leaving the
+/// modal, and Escape dismisses it when that is allowed. Other keys are left
+/// to the browser.
+fn key_action(key: &str, shift: bool, focused: Option<usize>, count: usize, dismissible: bool) -> Option<KeyAction> {
+    match key {
+        "Escape" if dismissible => Some(KeyAction::Dismiss),
+        "Tab" if count == 0 => Some(KeyAction::Stay),
+        "Tab" => Some(KeyAction::Focus(match (focused, shift) {
+            (Some(i), false) => (i + 1) % count,
+            (Some(i), true) => (i + count - 1) % count,
+            (None, false) => 0,
+            (None, true) => count - 1,
+        })),
+        _ => None,
+    }
+}
+
+/// A labelled text input.
+pub struct Field {
+    pub id: &'static str,
+    pub label: String,
+    pub value: String,
+}
+
+/// A footer button raising `event` when clicked.
+pub struct Action {
+    pub label: String,
+    pub event: Event,
+    pub primary: bool,
+}
+
+pub struct Modal {
+    pub title: String,
+    pub paragraphs: Vec<String>,
+    pub fields: Vec<Field>,
+    pub actions: Vec<Action>,
+    /// Raised when the modal is closed without a button. Modals without
+    /// one cannot be closed that way.
+    pub dismiss: Option<Event>,
+}
+
+impl Modal {
+    pub fn new(title: impl Into<String>) -> Modal {
+        Modal {
+            title: title.into(),
+            paragraphs: Vec::new(),
+            fields: Vec::new(),
+            actions: Vec::new(),
+            dismiss: None,
+        }
+    }
+
+    pub fn text(mut self, text: impl Into<String>) -> Modal {
+        self.paragraphs.push(text.into());
+        self
+    }
+
+    pub fn field(mut self, id: &'static str, label: impl Into<String>, value: impl Into<String>) -> Modal {
+        self.fields.push(Field {
+            id,
+            label: label.into(),
+            value: value.into(),
+        });
+        self
+    }
+
+    pub fn action(mut self, label: impl Into<String>, event: Event) -> Modal {
+        self.actions.push(Action {
+            label: label.into(),
+            event,
+            primary: false,
+        });
+        self
+    }
+
+    /// Adds the button that submitting the form also presses.
+    pub fn primary(mut self, label: impl Into<String>, event: Event) -> Modal {
+        self.actions.push(Action {
+            label: label.into(),
+            event,
+            primary: true,
+        });
+        self
+    }
+
+    pub fn dismiss(mut self, event: Event) -> Modal {
+        self.dismiss = Some(event);
+        self
+    }
+
+    /// The `#status` element. Buttons carry the index of their action, for
+    /// the listeners to be attached once the element exists.
+    pub fn element(&self) -> dom::Element {
+        let mut body = dom::element("div")
+            .class("modal-body");
+
+        for paragraph in self.paragraphs.iter() {
+            body = body.child(dom::element("p").text(paragraph.clone()));
+        }
+
+        if ! self.fields.is_empty() {
+            let mut form = dom::element("form");
+
+            for field in self.fields.iter() {
+                form = form.child(Modal::field_element(field));
+            }
+
+            body = body.child(form);
+        }
+
+        let mut footer = dom::element("div")
+            .class("modal-footer");
+
+        for (i, action) in self.actions.iter().enumerate() {
+            footer = footer.child(dom::element("button")
+                .class(if action.primary { "btn btn-primary" } else { "btn btn-secondary" })
+                .attribute("type", "button")
+                .attribute("data-action", i.to_string())
+                .text(action.label.clone()));
+        }
+
+        let modal = dom::element("div")
+            .id("status")
+            .class("modal fade")
+            .attribute("tabindex", "-1")
+            .attribute("role", "dialog")
+            .attribute("aria-modal", "true")
+            .attribute("aria-labelledby", "statusTitle");
+
+        modal.child(dom::element("div")
+            .class("modal-dialog")
+            .attribute("role", "document")
+            .child(dom::element("div")
+                .class("modal-content")
+                .child(dom::element("div")
+                    .class("modal-header")
+                    .child(dom::element("h1")
+                        .id("statusTitle")
+                        .text(self.title.clone())))
+                .child(body)
+                .child(footer)))
+    }
+
+    fn field_element(field: &Field) -> dom::Element {
+        let error_id = format!("{}Error", field.id);
+
+        dom::element("div")
+            .class("form-group")
+            .child(dom::element("div")
+                .class("form-row")
+                .child(dom::element("div")
+                    .class("col-4")
+                    .child(dom::element("label")
+                        .attribute("for", field.id)
+                        .text(field.label.clone())))
+                .child(dom::element("div")
+                    .class("col-8")
+                    .child(dom::element("input")
+                        .attribute("type", "text")
+                        .class("form-control")
+                        .id(field.id)
+                        .attribute("placeholder", field.label.clone())
+                        .attribute("value", field.value.clone())
+                        .attribute("aria-describedby", error_id.clone()))
+                    .child(dom::element("div")
+                        .class("invalid-feedback")
+                        .id(error_id))))
+    }
+
+    /// Shows the modal in place of any other, until the returned handle is
+    /// dropped.
+    pub fn open(&self, dispatcher: &Dispatcher) -> JsResult<OpenModal> {
+        let document = web_sys::window()
+            .unwrap()
+            .document()
+            .unwrap();
+
+        if let Some(status) = document.get_element_by_id("status") {
+            status.remove();
+        }
+
+        let element = self.element()
+            .build()?;
+
+        let mut listeners = Vec::new();
+
+        for (i, action) in self.actions.iter().enumerate() {
+            let button = element.query_selector(&format!("[data-action=\"{}\"]", i))?
+                .unwrap()
+                .dyn_into::<HtmlElement>()?;
+
+            let on_click = dispatcher.callback(action.event.clone());
+
+            button.set_onclick(Some(on_click.as_ref().unchecked_ref()));
+
+            listeners.push(on_click);
+        }
+
+        // Enter in a field submits the form, which would reload the page.
+        let on_submit = match element.query_selector("form")? {
+            Some(form) => {
+                let primary = self.actions
+                    .iter()
+                    .find(|action| action.primary)
+                    .map(|action| action.event.clone());
+                let submit_dispatcher = dispatcher.clone();
+
+                let on_submit = Closure::wrap(Box::new(move |e: web_sys::Event| {
+                    e.prevent_default();
+
+                    if let Some(event) = primary.clone() {
+                        submit_dispatcher.dispatch(event);
+                    }
+
+                    Ok::<(), JsValue>(())
+                }) as Box<dyn FnMut(web_sys::Event) -> JsError>);
+
+                form.add_event_listener_with_callback("submit", on_submit.as_ref().unchecked_ref())?;
+
+                Some(on_submit)
+            }
+            None => None,
+        };
+
+        let dismissible = self.dismiss.is_some();
+
+        // Listening on the document catches keys even when focus has
+        // somehow left the modal.
+        let key_element = element.clone();
+        let key_dismiss = self.dismiss.clone();
+        let key_dispatcher = dispatcher.clone();
+        let on_key = Closure::wrap(Box::new(move |e: KeyboardEvent| {
+            let focusable = OpenModal::focusable(&key_element)?;
+            let active = web_sys::window()
+                .unwrap()
+                .document()
+                .unwrap()
+                .active_element();
+            let focused = focusable.iter()
+                .position(|candidate| active.as_ref().is_some_and(|active| **candidate == *active));
+
+            match key_action(&e.key(), e.shift_key(), focused, focusable.len(), dismissible) {
+                Some(KeyAction::Dismiss) => {
+                    e.prevent_default();
+                    key_dispatcher.dispatch(key_dismiss.clone().unwrap());
+                }
+                Some(KeyAction::Focus(i)) => {
+                    e.prevent_default();
+                    focusable[i].focus()?;
+                }
+                Some(KeyAction::Stay) =>
+                    e.prevent_default(),
+                None => (),
+            }
+
+            Ok::<(), JsValue>(())
+        }) as Box<dyn FnMut(KeyboardEvent) -> JsError>);
+
+        document.add_event_listener_with_callback("keydown", on_key.as_ref().unchecked_ref())?;
+
+        // The modal element covers the page around the dialog, so a click
+        // landing on it is a click on the backdrop.
+        let click_element = element.clone();
+        let click_dismiss = self.dismiss.clone();
+        let click_dispatcher = dispatcher.clone();
+        let on_click = Closure::wrap(Box::new(move |e: MouseEvent| {
+            let on_backdrop = e.target()
+                .is_some_and(|target| JsValue::from(target) == JsValue::from(&click_element));
+
+            if on_backdrop {
+                if let Some(event) = click_dismiss.clone() {
+                    click_dispatcher.dispatch(event);
+                }
+            }
+
+            Ok::<(), JsValue>(())
+        }) as Box<dyn FnMut(MouseEvent) -> JsError>);
+
+        element.add_event_listener_with_callback("click", on_click.as_ref().unchecked_ref())?;
+
+        let previous_focus = document.active_element()
+            .and_then(|active| active.dyn_into::<HtmlElement>().ok());
+
+        let backdrop = dom::element("div")
+            .class("modal-backdrop fade show")
+            .build()?;
+
+        let body = document.body()
+            .unwrap();
+
+        body.append_child(&backdrop)?;
+        body.append_child(&element)?;
+        body.class_list()
+            .add_1("modal-open")?;
+
+        let element = element.dyn_into::<HtmlElement>()?;
+        element.style()
+            .set_property("display", "block")?;
+
+        // Laying the modal out before it gets `show` lets it fade in.
+        element.offset_width();
+        element.class_list()
+            .add_1("show")?;
+
+        let open_modal = OpenModal {
+            element,
+            backdrop,
+            previous_focus,
+            _listeners: listeners,
+            _on_submit: on_submit,
+            on_key,
+            _on_click: on_click,
+        };
+
+        let first_field = self.fields
+            .first()
+            .map(|field| field.id);
+
+        match first_field {
+            Some(id) => open_modal.focus(id)?,
+            None => open_modal.focus_default()?,
+        }
+
+        Ok(open_modal)
+    }
+}
+
+/// A modal on screen. Dropping it closes the modal and gives focus back to
+/// where it was before.
+pub struct OpenModal {
+    element: HtmlElement,
+    backdrop: web_sys::Element,
+    previous_focus: Option<HtmlElement>,
+    _listeners: Vec<JsClosureNone>,
+    _on_submit: Option<JsClosure<web_sys::Event>>,
+    on_key: JsClosure<KeyboardEvent>,
+    _on_click: JsClosure<MouseEvent>,
+}
+
+impl OpenModal {
+    fn focusable(element: &web_sys::Element) -> JsResult<Vec<HtmlElement>> {
+        let found = element.query_selector_all(FOCUSABLE)?;
+
+        (0..found.length())
+            .filter_map(|i| found.item(i))
+            .map(|node| node.dyn_into::<HtmlElement>().map_err(JsValue::from))
+            .collect()
+    }
+
+    /// Focuses the primary button, or else the first thing that can take
+    /// focus, or else the modal itself.
+    fn focus_default(&self) -> JsError {
+        let primary = self.element
+            .query_selector(".btn-primary")?
+            .map(|primary| primary.dyn_into::<HtmlElement>())
+            .transpose()?;
+
+        match primary.or(OpenModal::focusable(&self.element)?.into_iter().next()) {
+            Some(focus) => focus.focus(),
+            None => self.element.focus(),
+        }
+    }
+
+    fn input(&self, id: &str) -> JsResult<HtmlInputElement> {
+        self.element
+            .query_selector(&format!("#{}", id))?
+            .ok_or_else(|| JsValue::from_str(&format!("No field {}", id)))?
+            .dyn_into::<HtmlInputElement>()
+            .map_err(JsValue::from)
+    }
+
+    pub fn value(&self, id: &str) -> JsResult<String> {
+        Ok(self.input(id)?.value())
+    }
+
+    /// Marks a field as invalid with `error`, or as fine without one.
+    pub fn set_error(&self, id: &str, error: Option<&str>) -> JsError {
+        let input = self.input(id)?;
+
+        input.set_class_name(if error.is_some() { "form-control is-invalid" } else { "form-control" });
+
+        self.element
+            .query_selector(&format!("#{}Error", id))?
+            .unwrap()
+            .set_text_content(error);
+
+        Ok(())
+    }
+
+    /// Reads a field through `check`, marking the field with `error` when
+    /// `check` turns the value down.
+    pub fn validate<T>(&self, id: &str, check: impl FnOnce(&str) -> Option<T>, error: &str) -> JsResult<Option<T>> {
+        let checked = check(&self.value(id)?);
+
+        self.set_error(id, match checked {
+            Some(_) => None,
+            None => Some(error),
+        })?;
+
+        Ok(checked)
+    }
+
+    /// Focuses a field with its text selected, for it to be typed over.
+    pub fn focus(&self, id: &str) -> JsError {
+        let input = self.input(id)?;
+
+        input.focus()?;
+        input.select();
+
+        Ok(())
+    }
+}
+
+impl Drop for OpenModal {
+    fn drop(&mut self) {
+        let document = web_sys::window()
+            .unwrap()
+            .document()
+            .unwrap();
+
+        let _ = document.remove_event_listener_with_callback("keydown", self.on_key.as_ref().unchecked_ref());
+
+        self.element.remove();
+        self.backdrop.remove();
+
+        if let Some(body) = document.body() {
+            let _ = body.class_list().remove_1("modal-open");
+        }
+
+        if let Some(previous_focus) = self.previous_focus.as_ref() {
+            let _ = previous_focus.focus();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dom::tests::{
+        tags,
+        text_content,
+    };
+
+    /// Elements with `tag` in the tree, depth first.
+    fn find<'a>(element: &'a dom::Element, tag: &str) -> Vec<&'a dom::Element> {
+        let mut found = Vec::new();
+
+        if element.tag == tag {
+            found.push(element);
+        }
+
+        for child in element.children.iter() {
+            if let dom::Node::Element(child) = child {
+                found.extend(find(child, tag));
+            }
+        }
+
+        found
+    }
+
+    fn attribute<'a>(element: &'a dom::Element, name: &str) -> Option<&'a str> {
+        element.attributes
+            .iter()
+            .find(|(other, _)| *other == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    #[test]
+    fn buttons_carry_the_index_of_their_action() {
+        let modal = Modal::new("Session Moved")
+            .action("Quit", Event::StatusQuit)
+            .primary("Use This Tab", Event::TakeoverAccept);
+
+        let element = modal.element();
+        let buttons = find(&element, "button");
+
+        assert_eq!(buttons.len(), 2);
+        assert_eq!(attribute(buttons[0], "data-action"), Some("0"));
+        assert_eq!(attribute(buttons[0], "class"), Some("btn btn-secondary"));
+        assert_eq!(attribute(buttons[1], "data-action"), Some("1"));
+        assert_eq!(attribute(buttons[1], "class"), Some("btn btn-primary"));
+    }
+
+    #[test]
+    fn element_lays_out_fields_and_actions() {
+        let modal = Modal::new("Create")
+            .field("roomName", "Room Name", "go")
+            .action("Quit", Event::StatusQuit)
+            .primary("Create", Event::RoomCreateSubmit);
+
+        let element = modal.element();
+
+        assert_eq!(tags(&element), vec![
+            "div", "div", "div", "div", "h1",
+            "div", "form", "div", "div", "div", "label", "div", "input", "div",
+            "div", "button", "button",
+        ]);
+        assert_eq!(text_content(&element), "CreateRoom NameQuitCreate");
+    }
+
+    #[test]
+    fn tab_cycles_within_the_modal() {
+        assert_eq!(key_action("Tab", false, Some(0), 3, false), Some(KeyAction::Focus(1)));
+        assert_eq!(key_action("Tab", false, Some(2), 3, false), Some(KeyAction::Focus(0)));
+        assert_eq!(key_action("Tab", true, Some(0), 3, false), Some(KeyAction::Focus(2)));
+        assert_eq!(key_action("Tab", true, None, 3, false), Some(KeyAction::Focus(2)));
+        assert_eq!(key_action("Tab", false, None, 0, false), Some(KeyAction::Stay));
+    }
+
+    #[test]
+    fn escape_dismisses_only_dismissible_modals() {
+        assert_eq!(key_action("Escape", false, None, 2, true), Some(KeyAction::Dismiss));
+        assert_eq!(key_action("Escape", false, None, 2, false), None);
+        assert_eq!(key_action("Enter", false, Some(1), 2, true), None);
+    }
+}
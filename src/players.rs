@@ -0,0 +1,231 @@
+//! The player and spectator tables of the status bar. Rows are kept by
+//! username in a map instead of being looked up by element id, so usernames
+//! may hold any characters and rows that are already gone are just skipped.
+
+use {
+    crate::{
+        dom,
+        i18n::{
+            Locale,
+            Message,
+        },
+    },
+    rengo_common::logic::Stone,
+    std::collections::HashMap,
+    wasm_bindgen::{
+        prelude::*,
+        JsCast,
+    },
+    web_sys::HtmlElement,
+};
+
+/// How a row stands out from the others.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Highlight {
+    None,
+    Turn,
+    Passed,
+}
+
+impl Highlight {
+    fn colour(self) -> &'static str {
+        match self {
+            Highlight::None => "#ffffff",
+            Highlight::Turn => "grey",
+            Highlight::Passed => "red",
+        }
+    }
+}
+
+/// Row for `username`, with their stone colour when they are playing.
+/// Usernames come from other clients, so they only go in as text.
+pub fn row(username: &str, stone: Option<Stone>, locale: Locale) -> dom::Element {
+    let row = dom::element("tr")
+        .child(dom::element("th").text(username));
+
+    match stone {
+        Some(stone) => row.child(dom::element("th").text(locale.text(Message::StoneColour(stone)))),
+        None => row,
+    }
+}
+
+/// A row the list keeps, i.e. a table row in the page.
+pub trait Row {
+    fn remove(&self);
+    fn highlight(&self, highlight: Highlight) -> Result<(), JsValue>;
+}
+
+impl Row for HtmlElement {
+    fn remove(&self) {
+        web_sys::Element::remove(self);
+    }
+
+    fn highlight(&self, highlight: Highlight) -> Result<(), JsValue> {
+        self.style().set_property("background-color", highlight.colour())
+    }
+}
+
+pub struct PlayerList<R: Row = HtmlElement> {
+    rows: HashMap<String, R>,
+}
+
+impl<R: Row> Default for PlayerList<R> {
+    fn default() -> PlayerList<R> {
+        PlayerList {
+            rows: HashMap::new(),
+        }
+    }
+}
+
+impl PlayerList {
+    /// Appends a row for `username` to `table`, replacing any row they
+    /// already had.
+    pub fn add(&mut self, table: &web_sys::Element, username: &str, stone: Option<Stone>, locale: Locale) -> Result<(), JsValue> {
+        let new_row = row(username, stone, locale)
+            .build()?
+            .dyn_into::<HtmlElement>()?;
+
+        table.append_child(&new_row)?;
+
+        self.insert(username, new_row);
+
+        Ok(())
+    }
+}
+
+impl<R: Row> PlayerList<R> {
+    /// Keeps `row` as the row of `username`, removing any row they already
+    /// had.
+    pub fn insert(&mut self, username: &str, row: R) {
+        if let Some(old_row) = self.rows.insert(username.to_string(), row) {
+            old_row.remove();
+        }
+    }
+
+    /// Removes the row of `username`, if there is one.
+    pub fn remove(&mut self, username: &str) {
+        if let Some(old_row) = self.rows.remove(username) {
+            old_row.remove();
+        }
+    }
+
+    /// Highlights the row of `username`, if there is one.
+    pub fn highlight(&self, username: &str, highlight: Highlight) -> Result<(), JsValue> {
+        match self.rows.get(username) {
+            Some(row) => row.highlight(highlight),
+            None => Ok(()),
+        }
+    }
+
+    /// Highlights only the row of `username`, if there is one.
+    pub fn highlight_only(&self, username: &str, highlight: Highlight) -> Result<(), JsValue> {
+        self.clear_highlights()?;
+        self.highlight(username, highlight)
+    }
+
+    pub fn clear_highlights(&self) -> Result<(), JsValue> {
+        for row in self.rows.values() {
+            row.highlight(Highlight::None)?;
+        }
+
+        Ok(())
+    }
+
+    /// Removes every row.
+    pub fn clear(&mut self) {
+        for (_, old_row) in self.rows.drain() {
+            old_row.remove();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        cell::Cell,
+        rc::Rc,
+    };
+
+    /// A row that shares its state with the test, so it can still be looked
+    /// at after the list dropped it.
+    #[derive(Clone, Default)]
+    struct TestRow {
+        removed: Rc<Cell<bool>>,
+        highlight: Rc<Cell<Option<Highlight>>>,
+    }
+
+    impl Row for TestRow {
+        fn remove(&self) {
+            self.removed.set(true);
+        }
+
+        fn highlight(&self, highlight: Highlight) -> Result<(), JsValue> {
+            self.highlight.set(Some(highlight));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn remove_takes_out_the_row_of_that_username() {
+        let mut list = PlayerList::default();
+        let (ann, bob) = (TestRow::default(), TestRow::default());
+
+        list.insert("ann", ann.clone());
+        list.insert("bob", bob.clone());
+        list.remove("ann");
+
+        assert!(ann.removed.get());
+        assert!(! bob.removed.get());
+        assert_eq!(list.rows.len(), 1);
+    }
+
+    #[test]
+    fn remove_skips_unknown_usernames() {
+        let mut list = PlayerList::default();
+        let ann = TestRow::default();
+
+        list.insert("ann", ann.clone());
+        list.remove("bob");
+        list.remove("Ann");
+
+        assert!(! ann.removed.get());
+        assert_eq!(list.rows.len(), 1);
+    }
+
+    #[test]
+    fn highlight_only_clears_the_other_rows() {
+        let mut list = PlayerList::default();
+        let (ann, bob) = (TestRow::default(), TestRow::default());
+
+        list.insert("ann", ann.clone());
+        list.insert("bob", bob.clone());
+        list.highlight("ann", Highlight::Passed).unwrap();
+        list.highlight_only("bob", Highlight::Turn).unwrap();
+
+        assert_eq!(ann.highlight.get(), Some(Highlight::None));
+        assert_eq!(bob.highlight.get(), Some(Highlight::Turn));
+
+        list.highlight_only("carol", Highlight::Turn).unwrap();
+
+        assert_eq!(bob.highlight.get(), Some(Highlight::None));
+    }
+
+    #[test]
+    fn adding_a_username_again_replaces_its_row() {
+        let mut list = PlayerList::default();
+        let (first, second) = (TestRow::default(), TestRow::default());
+
+        list.insert("ann", first.clone());
+        list.insert("ann", second.clone());
+
+        assert!(first.removed.get());
+        assert!(! second.removed.get());
+        assert_eq!(list.rows.len(), 1);
+
+        list.remove("ann");
+
+        assert!(second.removed.get());
+        assert!(list.rows.is_empty());
+    }
+}